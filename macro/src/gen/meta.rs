@@ -8,71 +8,191 @@ use crate::ir::{self, Expr, FieldKind, LeafKind, MapKey};
 
 /// Generates the whole `const META: ... = ...;` item.
 pub(super) fn gen(input: &ir::Input) -> TokenStream {
-    fn env_tokens(env: &Option<String>) -> TokenStream {
-        match env {
-            Some(key) => quote! { std::option::Option::Some(#key) },
-            None => quote! { std::option::Option::None },
-        }
+    fn env_tokens(s: &Option<String>) -> TokenStream {
+        super::option_tokens(s.as_deref())
     }
 
     let name_str = input.name.to_string();
     let doc = &input.doc;
     let meta_fields = input.fields.iter().map(|f| {
-        let name = f.name.to_string();
+        let name = &f.key;
         let doc =  &f.doc;
         let kind = match &f.kind {
-            FieldKind::Nested { ty } => {
-                quote! {
-                    confique::meta::FieldKind::Nested { meta: &<#ty as confique::Config>::META }
-                }
-            }
-            FieldKind::Leaf { env, kind: LeafKind::Optional { .. }, ..} => {
-                let env = env_tokens(env);
+            FieldKind::Nested { ty, env_prefix } => {
+                let env_prefix = env_tokens(env_prefix);
                 quote! {
-                    confique::meta::FieldKind::Leaf {
-                        env: #env,
-                        kind: confique::meta::LeafKind::Optional,
+                    confique::meta::FieldKind::Nested {
+                        meta: &<#ty as confique::Config>::META,
+                        env_prefix: #env_prefix,
                     }
                 }
             }
-            FieldKind::Leaf { env, kind: LeafKind::Required { default, ty, .. }, ..} => {
+            FieldKind::Leaf(leaf) => {
+                let ir::LeafField {
+                    env,
+                    env_aliases,
+                    env_file_suffix,
+                    credential,
+                    constraints,
+                    deserialize_with,
+                    kind,
+                    ..
+                } = &**leaf;
+                let ty = field_type_tokens(kind.inner_ty(), deserialize_with.is_some());
                 let env = env_tokens(env);
-                let default_value = match default {
-                    Some(default) => {
-                        let meta = default_value_to_meta_expr(default, Some(&ty));
-                        quote! { std::option::Option::Some(#meta) }
-                    },
-                    None => quote! { std::option::Option::None },
+                let credential = env_tokens(credential);
+                let constraints = constraints_tokens(constraints, kind.inner_ty());
+                let kind = match kind {
+                    LeafKind::Optional { .. } => quote! { confique::meta::LeafKind::Optional },
+                    LeafKind::Required { default, ty, .. } => {
+                        let default_value = match default {
+                            Some(default) => {
+                                let meta = default_value_to_meta_expr(default, Some(ty));
+                                quote! { std::option::Option::Some(#meta) }
+                            },
+                            None => quote! { std::option::Option::None },
+                        };
+                        quote! {
+                            confique::meta::LeafKind::Required {
+                                default: #default_value,
+                            }
+                        }
+                    }
                 };
                 quote! {
                     confique::meta::FieldKind::Leaf {
                         env: #env,
-                        kind: confique::meta::LeafKind::Required {
-                            default: #default_value,
-                        },
+                        env_aliases: &[ #(#env_aliases),* ],
+                        env_file_suffix: #env_file_suffix,
+                        credential: #credential,
+                        constraints: #constraints,
+                        ty: #ty,
+                        kind: #kind,
                     }
                 }
             }
         };
 
+        let secret = f.secret;
+        let aliases = &f.aliases;
+        let deprecated = env_tokens(&f.deprecated);
         quote! {
             confique::meta::Field {
                 name: #name,
                 doc: &[ #(#doc),* ],
+                secret: #secret,
+                aliases: &[ #(#aliases),* ],
+                deprecated: #deprecated,
                 kind: #kind,
             }
         }
     });
 
+    let env_prefix = super::option_tokens(input.env_prefix.as_deref());
     quote! {
         const META: confique::meta::Meta = confique::meta::Meta {
             name: #name_str,
             doc: &[ #(#doc),* ],
+            env_prefix: #env_prefix,
             fields: &[ #( #meta_fields ),* ],
         };
     }
 }
 
+/// Generates the `&[meta::Constraint]` expression for a leaf field with the
+/// (inner) type `ty`.
+fn constraints_tokens(constraints: &[ir::Constraint], ty: &syn::Type) -> TokenStream {
+    let constraints = constraints.iter().map(|c| match c {
+        ir::Constraint::Min(v) => {
+            let v = default_value_to_meta_expr(v, Some(ty));
+            quote! { confique::meta::Constraint::Min(#v) }
+        }
+        ir::Constraint::Max(v) => {
+            let v = default_value_to_meta_expr(v, Some(ty));
+            quote! { confique::meta::Constraint::Max(#v) }
+        }
+        ir::Constraint::OneOf(items) => {
+            let items = items.iter().map(|v| default_value_to_meta_expr(v, Some(ty)));
+            quote! { confique::meta::Constraint::OneOf(&[#( #items ),*]) }
+        }
+        ir::Constraint::NonEmpty => quote! { confique::meta::Constraint::NonEmpty },
+    });
+    quote! { &[#( #constraints ),*] }
+}
+
+/// Generates the `meta::Type` expression for a leaf field with the (inner)
+/// type `ty`.
+fn field_type_tokens(ty: &syn::Type, deserialize_with: bool) -> TokenStream {
+    if deserialize_with {
+        quote! { confique::meta::Type::Any }
+    } else {
+        type_tokens(ty)
+    }
+}
+
+/// Infers the `meta::Type` from the Rust type `ty`, just by looking at its
+/// name. Unknown types result in `Type::Any`.
+fn type_tokens(ty: &syn::Type) -> TokenStream {
+    let any = quote! { confique::meta::Type::Any };
+    let path = match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path,
+        syn::Type::Array(array) => {
+            let item = type_tokens(&array.elem);
+            return quote! { confique::meta::Type::Array(&#item) };
+        }
+        syn::Type::Group(g) => return type_tokens(&g.elem),
+        syn::Type::Paren(p) => return type_tokens(&p.elem),
+        _ => return any,
+    };
+
+    let last = path.segments.last().expect("empty type path");
+    let args = match &last.arguments {
+        syn::PathArguments::None => vec![],
+        syn::PathArguments::AngleBracketed(args) => args.args.iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect(),
+        syn::PathArguments::Parenthesized(_) => return any,
+    };
+
+    let name = last.ident.to_string();
+    match (&*name, &*args) {
+        ("bool", []) => quote! { confique::meta::Type::Bool },
+        ("f32" | "f64", []) => quote! { confique::meta::Type::Float },
+        ("String" | "str" | "char" | "PathBuf" | "IpAddr" | "Ipv4Addr" | "Ipv6Addr"
+            | "SocketAddr" | "SocketAddrV4" | "SocketAddrV6", []) => {
+            quote! { confique::meta::Type::String }
+        }
+        (_, []) => match int_type_to_variant(&name) {
+            Some(variant) => {
+                let variant = Ident::new(variant, Span::call_site());
+                // The primitive is named explicitly, as `#ty::MIN` would not
+                // compile if the user shadowed the name with their own type.
+                let ty = &last.ident;
+                quote! {
+                    confique::meta::Type::Integer {
+                        min: confique::meta::Integer::#variant(::core::primitive::#ty::MIN),
+                        max: confique::meta::Integer::#variant(::core::primitive::#ty::MAX),
+                    }
+                }
+            }
+            None => any,
+        },
+        ("Vec" | "VecDeque" | "LinkedList" | "HashSet" | "BTreeSet" | "BinaryHeap", [item]) => {
+            let item = type_tokens(item);
+            quote! { confique::meta::Type::Array(&#item) }
+        }
+        ("HashMap" | "BTreeMap", [_, value]) => {
+            let value = type_tokens(value);
+            quote! { confique::meta::Type::Map(&#value) }
+        }
+        ("Box" | "Rc" | "Arc", [inner]) => type_tokens(inner),
+        _ => any,
+    }
+}
+
 /// Helper macro to deduplicate logic for literals. Only used in the function
 /// below.
 macro_rules! match_literals {
@@ -11,10 +11,173 @@ mod meta;
 pub(crate) fn gen(input: ir::Input) -> TokenStream {
     let partial_mod = gen_partial_mod(&input);
     let config_impl = gen_config_impl(&input);
+    let debug_impls = match input.debug {
+        true => gen_debug_impls(&input),
+        false => gen_not_debug_check(&input),
+    };
+    let serialize_impl = if input.serialize { gen_serialize_impl(&input) } else { quote! {} };
 
     quote! {
         #config_impl
         #partial_mod
+        #debug_impls
+        #serialize_impl
+    }
+}
+
+/// Generates a non-capturing closure checking the given constraint, to be
+/// passed to `internal::validate_field`.
+fn gen_constraint_check(constraint: &ir::Constraint) -> TokenStream {
+    let (check, msg) = match constraint {
+        ir::Constraint::Min(v) => {
+            let v_tokens = default_value_to_deserializable_expr(v);
+            (quote! { *v >= #v_tokens }, format!("must be at least {}", v.display()))
+        }
+        ir::Constraint::Max(v) => {
+            let v_tokens = default_value_to_deserializable_expr(v);
+            (quote! { *v <= #v_tokens }, format!("must be at most {}", v.display()))
+        }
+        ir::Constraint::OneOf(items) => {
+            let list = items.iter().map(|v| v.display()).collect::<Vec<_>>().join(", ");
+            let items = items.iter().map(default_value_to_deserializable_expr);
+            (
+                quote! { confique::internal::is_one_of(v, &[#( #items ),*]) },
+                format!("must be one of: {list}"),
+            )
+        }
+        ir::Constraint::NonEmpty => (quote! { !v.is_empty() }, "must not be empty".into()),
+    };
+
+    quote! { |v| confique::internal::check(#check, #msg) }
+}
+
+/// Generates a check that fails to compile if the struct has secret fields and
+/// implements `Debug` (e.g. via `#[derive(Debug)]`, which cannot be detected
+/// by this derive if listed before `Config`), as that would print the secret
+/// values.
+fn gen_not_debug_check(input: &ir::Input) -> TokenStream {
+    let Some(secret) = input.fields.iter().find(|f| f.secret) else {
+        return quote! {};
+    };
+
+    let name = &input.name;
+    quote_spanned! {secret.name.span()=>
+        // If you get an error "type annotations needed" here: structs with
+        // `#[config(secret)]` fields must not derive `Debug`, use
+        // `#[config(debug)]` instead.
+        const _: fn() = || {
+            let _secret_fields_require_config_debug_instead_of_derive_debug
+                = <#name as confique::internal::NotDebug<_>>::check;
+        };
+    }
+}
+
+/// Generates `Debug` impls for the config struct and the partial type, which
+/// print `"<redacted>"` instead of the values of secret fields.
+fn gen_debug_impls(input: &ir::Input) -> TokenStream {
+    let name = &input.name;
+    let name_str = name.to_string();
+    let (partial_mod_name, partial_struct_name) = partial_names(&input.name);
+    let partial_name_str = partial_struct_name.to_string();
+
+    let config_fields = input.fields.iter().map(|f| {
+        let field_name = &f.name;
+        let field_str = field_name.to_string();
+        if f.secret {
+            quote! { .field(#field_str, &confique::internal::Redacted) }
+        } else {
+            quote! { .field(#field_str, &self.#field_name) }
+        }
+    });
+    let partial_fields = input.fields.iter().map(|f| {
+        let field_name = &f.name;
+        let field_str = field_name.to_string();
+        if f.secret {
+            quote! {
+                .field(#field_str, &std::option::Option::map(
+                    std::option::Option::as_ref(&self.#field_name),
+                    |_| confique::internal::Redacted,
+                ))
+            }
+        } else {
+            quote! { .field(#field_str, &self.#field_name) }
+        }
+    });
+    let nested_bounds = input.fields.iter().filter_map(|f| match &f.kind {
+        FieldKind::Nested { ty, .. } => {
+            Some(quote! { <#ty as confique::Config>::Partial: std::fmt::Debug })
+        }
+        FieldKind::Leaf(_) => None,
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl std::fmt::Debug for #name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(#name_str)
+                    #( #config_fields )*
+                    .finish()
+            }
+        }
+
+        #[automatically_derived]
+        impl std::fmt::Debug for #partial_mod_name::#partial_struct_name
+        where
+            #( #nested_bounds, )*
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(#partial_name_str)
+                    #( #partial_fields )*
+                    .finish()
+            }
+        }
+    }
+}
+
+/// Generates a `Serialize` impl for the config struct, using the same field
+/// names as `META` (i.e. respecting `rename` and `rename_all`). Secret values
+/// are serialized as `"<redacted>"`.
+fn gen_serialize_impl(input: &ir::Input) -> TokenStream {
+    let name = &input.name;
+    let name_str = name.to_string();
+    let num_fields = input.fields.len();
+    let fields = input.fields.iter().map(|f| {
+        let field_name = &f.name;
+        let key = &f.key;
+        let value = match &f.kind {
+            FieldKind::Leaf(leaf) if f.secret => match leaf.kind {
+                LeafKind::Optional { .. } => quote! {
+                    &self.#field_name.as_ref().map(|_| confique::internal::Redacted)
+                },
+                LeafKind::Required { .. } => quote! { &confique::internal::Redacted },
+            },
+            _ => quote! { &self.#field_name },
+        };
+        quote! {
+            confique::serde::ser::SerializeStruct::serialize_field(
+                &mut s,
+                #key,
+                #value,
+            )?;
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl confique::serde::Serialize for #name {
+            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where
+                S: confique::serde::Serializer,
+            {
+                let mut s = confique::serde::Serializer::serialize_struct(
+                    serializer,
+                    #name_str,
+                    #num_fields,
+                )?;
+                #( #fields )*
+                confique::serde::ser::SerializeStruct::end(s)
+            }
+        }
     }
 }
 
@@ -26,27 +189,54 @@ fn gen_config_impl(input: &ir::Input) -> TokenStream {
     let field_names = input.fields.iter().map(|f| &f.name);
     let from_exprs = input.fields.iter().map(|f| {
         let field_name = &f.name;
-        let path = field_name.to_string();
-        match f.kind {
+        let path = &f.key;
+        match &f.kind {
             FieldKind::Nested { .. } => {
                 quote! {
                     confique::internal::map_err_prefix_path(
                         confique::Config::from_partial(partial.#field_name),
                         #path,
-                    )?
+                    )
                 }
             }
-            FieldKind::Leaf { kind: LeafKind::Optional { .. }, .. } => {
-                quote! { partial.#field_name }
-            }
-            FieldKind::Leaf { kind: LeafKind::Required { .. }, .. } => {
-                quote! {
-                    confique::internal::unwrap_or_missing_value_err(partial.#field_name, #path)?
+            FieldKind::Leaf(leaf) => match leaf.kind {
+                LeafKind::Optional { .. } => {
+                    quote! { std::result::Result::Ok(partial.#field_name) }
                 }
-            }
+                LeafKind::Required { .. } => quote! {
+                    confique::internal::unwrap_or_missing_value_err(partial.#field_name, #path)
+                },
+            },
         }
     });
-
+    let field_indices = (0..input.fields.len()).map(syn::Index::from).collect::<Vec<_>>();
+
+
+    let field_validations = input.fields.iter().zip(&field_indices).flat_map(|(f, idx)| {
+        let FieldKind::Leaf(leaf) = &f.kind else {
+            return vec![];
+        };
+        let ir::LeafField { validate, constraints, kind, .. } = &**leaf;
+        let path = &f.key;
+        let fn_name = match kind {
+            LeafKind::Required { .. } => quote! { validate_field },
+            LeafKind::Optional { .. } => quote! { validate_optional_field },
+        };
+
+        // Constraints are checked before the custom validation function.
+        constraints.iter()
+            .map(gen_constraint_check)
+            .chain(validate.as_ref().map(|validate| quote! { #validate }))
+            .map(|validate| quote! {
+                if let std::option::Option::Some(v) = &values.#idx {
+                    errors.collect(confique::internal::#fn_name(v, #path, #validate));
+                }
+            })
+            .collect()
+    });
+    let struct_validation = input.validate.as_ref().map(|validate| quote! {
+        confique::internal::validate_struct(&out, #validate)?;
+    });
 
     let meta_item = meta::gen(input);
     quote! {
@@ -55,13 +245,32 @@ fn gen_config_impl(input: &ir::Input) -> TokenStream {
             type Partial = #partial_mod_name::#partial_struct_name;
 
             fn from_partial(partial: Self::Partial) -> std::result::Result<Self, confique::Error> {
-                std::result::Result::Ok(Self {
-                    #( #field_names: #from_exprs, )*
-                })
+                // All fields are loaded and validated before returning, so
+                // that all errors are reported at once.
+                #[allow(unused_mut)]
+                let mut errors = confique::internal::ErrorCollector::new();
+                #[allow(unused_variables)]
+                let values = ( #( errors.collect(#from_exprs), )* );
+                #( #field_validations )*
+                errors.finish()?;
+
+                let out = Self {
+                    #( #field_names: values.#field_indices.unwrap(), )*
+                };
+                #struct_validation
+                std::result::Result::Ok(out)
             }
 
             #meta_item
         }
+
+        // Defined here instead of in the partial module, as the config type
+        // cannot be named there if it is defined inside a function.
+        impl #partial_mod_name::#partial_struct_name {
+            fn __confique_meta() -> &'static confique::meta::Meta {
+                &<#name as confique::Config>::META
+            }
+        }
     }
 }
 
@@ -78,23 +287,28 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
 
     // Prepare some tokens per field.
     let field_names = input.fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let field_indices = (0..input.fields.len()).map(syn::Index::from).collect::<Vec<_>>();
     let struct_fields = input.fields.iter().map(|f| {
         let name = &f.name;
 
         // We have to use the span of the field's name here so that error
         // messages from the `derive(serde::Deserialize)` have the correct span.
         let inner_vis = inner_visibility(&input.visibility, name.span());
-        match &f.kind {
-            FieldKind::Leaf { kind, deserialize_with, .. } => {
-                let ty = kind.inner_ty();
-                let attr = match deserialize_with {
-                    None => quote! {},
-                    Some(p) => {
-                        let fn_name = deserialize_fn_name(&f.name).to_string();
-                        quote_spanned! {p.span()=>
-                            #[serde(default, deserialize_with = #fn_name)]
-                        }
-                    }
+        let rename = (*name != f.key).then(|| {
+            let key = &f.key;
+            quote! { #[serde(rename = #key)] }
+        });
+        let aliases = &f.aliases;
+        let aliases = quote! { #rename #( #[serde(alias = #aliases)] )* };
+        let field = match &f.kind {
+            FieldKind::Leaf(leaf) => {
+                let ty = leaf.kind.inner_ty();
+                let fn_name = deserialize_fn_name(&f.name).to_string();
+                let attr = match &leaf.deserialize_with {
+                    None => quote! { #[serde(default, deserialize_with = #fn_name)] },
+                    Some(p) => quote_spanned! {p.span()=>
+                        #[serde(default, deserialize_with = #fn_name)]
+                    },
                 };
 
                 let main = quote_spanned! {name.span()=>
@@ -102,15 +316,17 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
                 };
                 quote! { #attr #main }
             }
-            FieldKind::Nested { ty } => {
+            FieldKind::Nested { ty, .. } => {
                 let ty_span = ty.span();
                 let field_ty = quote_spanned! {ty_span=> <#ty as confique::Config>::Partial };
+                let fn_name = deserialize_fn_name(&f.name).to_string();
                 quote! {
-                    #[serde(default = "confique::Partial::empty")]
+                    #[serde(default = "confique::Partial::empty", deserialize_with = #fn_name)]
                     #inner_vis #name: #field_ty
                 }
             },
-        }
+        };
+        quote! { #aliases #field }
     });
 
     let empty_values = input.fields.iter().map(|f| {
@@ -123,19 +339,18 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
 
     let defaults = input.fields.iter().map(|f| {
         match &f.kind {
-            FieldKind::Leaf {
-                kind: LeafKind::Required { default: Some(default), .. },
-                deserialize_with,
-                ..
-            } => {
+            FieldKind::Leaf(leaf) => {
+                let LeafKind::Required { default: Some(default), .. } = &leaf.kind else {
+                    return quote! { std::option::Option::None };
+                };
                 let msg = format!(
                     "default config value for `{}::{}` cannot be deserialized",
                     input.name,
                     f.name,
                 );
-                let expr = default_value_to_deserializable_expr(&default);
+                let expr = default_value_to_deserializable_expr(default);
 
-                match deserialize_with {
+                match &leaf.deserialize_with {
                     None => quote! {
                         std::option::Option::Some(
                             confique::internal::deserialize_default(#expr).expect(#msg)
@@ -148,30 +363,51 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
                     },
                 }
             }
-            FieldKind::Leaf { .. } => quote! { std::option::Option::None },
             FieldKind::Nested { .. } => quote! { confique::Partial::default_values() },
         }
     });
 
     let from_env_fields = input.fields.iter().map(|f| {
+        let name_str = &f.key;
         match &f.kind {
-            FieldKind::Leaf { env: Some(key), deserialize_with, parse_env, .. } => {
-                let field = format!("{}::{}", input.name, f.name);
+            FieldKind::Leaf(leaf) => {
+                let ir::LeafField {
+                    env, env_aliases, env_file_suffix, deserialize_with, parse_env, ..
+                } = &**leaf;
+                let env = option_tokens(env.as_deref());
+                let mut key = quote! { scope.var(#env, #name_str, #env_file_suffix) };
+                if !env_aliases.is_empty() {
+                    key = quote! {
+                        confique::internal::env_var_with_aliases(
+                            source, scope, #key, &[#( #env_aliases ),*])
+                    };
+                }
+                let field = quote! { &scope.path(#name_str) };
+                let secret = f.secret;
                 match (parse_env, deserialize_with) {
                     (None, None) => quote! {
-                        confique::internal::from_env(#key, #field)?
+                        confique::internal::from_env(source, #key, #field, #secret)
                     },
                     (None, Some(deserialize_with)) => quote! {
                         confique::internal::from_env_with_deserializer(
-                            #key, #field, #deserialize_with)?
+                            source, #key, #field, #secret, #deserialize_with)
                     },
                     (Some(parse_env), _) => quote! {
-                        confique::internal::from_env_with_parser(#key, #field, #parse_env)?
+                        confique::internal::from_env_with_parser(
+                            source, #key, #field, #secret, #parse_env)
                     },
                 }
             }
-            FieldKind::Leaf { .. } => quote! { std::option::Option::None },
-            FieldKind::Nested { .. } => quote! { confique::Partial::from_env()? },
+            FieldKind::Nested { ty, env_prefix } => {
+                let env_prefix = option_tokens(env_prefix.as_deref());
+                quote! {
+                    confique::Partial::from_env_scoped(source, &scope.nested(
+                        #name_str,
+                        #env_prefix,
+                        <#ty as confique::Config>::META.env_prefix,
+                    ))
+                }
+            }
         }
     });
 
@@ -196,8 +432,8 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
     let is_complete_expr = input.fields.iter().map(|f| {
         let name = &f.name;
         match &f.kind {
-            FieldKind::Leaf { kind, .. } => {
-                if kind.is_required() {
+            FieldKind::Leaf(leaf) => {
+                if leaf.kind.is_required() {
                     quote! { self.#name.is_some() }
                 } else {
                     quote! { true }
@@ -207,31 +443,63 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
         }
     });
 
-    let deserialize_fns = input.fields.iter().filter_map(|f| {
-        match &f.kind {
-            FieldKind::Leaf { kind, deserialize_with: Some(p), .. } => {
-                let fn_name = deserialize_fn_name(&f.name);
-                let ty = kind.inner_ty();
-
-                Some(quote! {
-                    fn #fn_name<'de, D>(
-                        deserializer: D,
-                    ) -> std::result::Result<std::option::Option<#ty>, D::Error>
-                    where
-                        D: serde::Deserializer<'de>,
-                    {
-                        #p(deserializer).map(std::option::Option::Some)
-                    }
-                })
+    let set_path_stmts = input.fields.iter().map(|f| {
+        let name = &f.name;
+        let path = &f.key;
+        if f.is_leaf() {
+            quote! {
+                if self.#name.is_some() {
+                    out.push(std::borrow::ToOwned::to_owned(#path));
+                }
+            }
+        } else {
+            quote! {
+                confique::internal::push_prefixed_paths(
+                    &mut out,
+                    #path,
+                    confique::Partial::set_paths(&self.#name),
+                );
+            }
+        }
+    });
+
+    // All fields are deserialized via these functions, which keep track of the
+    // path of the value being deserialized for error messages.
+    let deserialize_fns = input.fields.iter().map(|f| {
+        let fn_name = deserialize_fn_name(&f.name);
+        let name_str = &f.key;
+        let (ty, deserialize) = match &f.kind {
+            FieldKind::Leaf(leaf) => {
+                let ty = leaf.kind.inner_ty();
+                let ty = quote! { std::option::Option<#ty> };
+                match &leaf.deserialize_with {
+                    Some(p) => (ty, quote! { |de| #p(de).map(std::option::Option::Some) }),
+                    None => (
+                        ty.clone(),
+                        quote! { <#ty as confique::serde::Deserialize<'de>>::deserialize },
+                    ),
+                }
+            }
+            FieldKind::Nested { ty, .. } => {
+                let ty = quote! { <#ty as confique::Config>::Partial };
+                (ty.clone(), quote! { <#ty as confique::serde::Deserialize<'de>>::deserialize })
+            }
+        };
+
+        quote! {
+            fn #fn_name<'de, D>(deserializer: D) -> std::result::Result<#ty, D::Error>
+            where
+                D: confique::serde::Deserializer<'de>,
+            {
+                confique::internal::deserialize_field(#name_str, deserializer, #deserialize)
             }
-            _ => None,
         }
     });
 
     let nested_bounds = input.fields.iter().filter_map(|f| {
         match &f.kind {
-            FieldKind::Nested { ty } => Some(quote! { #ty: confique::Config }),
-            FieldKind::Leaf { .. } => None,
+            FieldKind::Nested { ty, .. } => Some(quote! { #ty: confique::Config }),
+            FieldKind::Leaf(_) => None,
         }
     });
 
@@ -247,6 +515,7 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
     );
 
     let partial_attrs = &input.partial_attrs;
+    let env_prefix = option_tokens(input.env_prefix.as_deref());
 
     quote! {
         #[doc = #module_doc]
@@ -274,9 +543,26 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
                     }
                 }
 
-                fn from_env() -> std::result::Result<Self, confique::Error> {
+                fn from_env_source(
+                    source: &dyn confique::env::EnvSource,
+                ) -> std::result::Result<Self, confique::Error> {
+                    confique::Partial::from_env_scoped(
+                        source,
+                        &confique::internal::EnvScope::root(#env_prefix),
+                    )
+                }
+
+                fn from_env_scoped(
+                    source: &dyn confique::env::EnvSource,
+                    scope: &confique::internal::EnvScope,
+                ) -> std::result::Result<Self, confique::Error> {
+                    #[allow(unused_mut)]
+                    let mut errors = confique::internal::ErrorCollector::new();
+                    #[allow(unused_variables)]
+                    let values = ( #( errors.collect(#from_env_fields), )* );
+                    errors.finish()?;
                     std::result::Result::Ok(Self {
-                        #( #field_names: #from_env_fields, )*
+                        #( #field_names: values.#field_indices.unwrap(), )*
                     })
                 }
 
@@ -293,6 +579,16 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
                 fn is_complete(&self) -> bool {
                     true #(&& #is_complete_expr)*
                 }
+
+                fn set_paths(&self) -> std::vec::Vec<std::string::String> {
+                    let mut out = std::vec::Vec::new();
+                    #( #set_path_stmts )*
+                    out
+                }
+
+                fn meta() -> &'static confique::meta::Meta {
+                    Self::__confique_meta()
+                }
             }
 
             #(#deserialize_fns)*
@@ -351,6 +647,14 @@ fn default_value_to_deserializable_expr(expr: &ir::Expr) -> TokenStream {
     }
 }
 
+/// Returns tokens of an `Option<&'static str>` expression.
+fn option_tokens(value: Option<&str>) -> TokenStream {
+    match value {
+        Some(v) => quote! { std::option::Option::Some(#v) },
+        None => quote! { std::option::Option::None },
+    }
+}
+
 /// Returns tokens defining the visibility of the items in the inner module.
 fn inner_visibility(outer: &syn::Visibility, span: Span) -> TokenStream {
     match outer {
@@ -8,6 +8,16 @@ pub(crate) struct Input {
     pub(crate) doc: Vec<String>,
     pub(crate) visibility: syn::Visibility,
     pub(crate) partial_attrs: Vec<TokenStream>,
+    pub(crate) env_prefix: Option<String>,
+
+    /// Whether to generate `Debug` impls for the struct and the partial type.
+    pub(crate) debug: bool,
+
+    /// Whether to generate a `Serialize` impl for the struct.
+    pub(crate) serialize: bool,
+
+    /// Function validating the whole struct, called in `from_partial`.
+    pub(crate) validate: Option<syn::Path>,
     pub(crate) name: syn::Ident,
     pub(crate) fields: Vec<Field>,
 }
@@ -15,6 +25,20 @@ pub(crate) struct Input {
 pub(crate) struct Field {
     pub(crate) doc: Vec<String>,
     pub(crate) name: syn::Ident,
+
+    /// The name of this field in configuration files, dotted paths and
+    /// `META`. Same as `name`, unless changed via `rename` or `rename_all`.
+    pub(crate) key: String,
+
+    /// Whether the value is secret and should never be printed. Only ever
+    /// `true` for leaf fields.
+    pub(crate) secret: bool,
+
+    /// Old names of this field that are still accepted in files.
+    pub(crate) aliases: Vec<String>,
+
+    /// Deprecation message, if the field is deprecated.
+    pub(crate) deprecated: Option<String>,
     pub(crate) kind: FieldKind,
 
     // TODO:
@@ -24,19 +48,50 @@ pub(crate) struct Field {
 }
 
 pub(crate) enum FieldKind {
-    Leaf {
-        env: Option<String>,
-        deserialize_with: Option<syn::Path>,
-        parse_env: Option<syn::Path>,
-        kind: LeafKind,
-    },
+    Leaf(Box<LeafField>),
 
     /// A nested configuration. The type is never `Option<_>`.
     Nested {
         ty: syn::Type,
+
+        /// Prefix for all env keys of the nested configuration, specific to
+        /// this field.
+        env_prefix: Option<String>,
     },
 }
 
+pub(crate) struct LeafField {
+    /// The env key given via `#[config(env = "...")]`, relative to the env
+    /// prefix in effect.
+    pub(crate) env: Option<String>,
+
+    /// Old env keys (relative like `env`) that are checked if the env variable
+    /// is not set.
+    pub(crate) env_aliases: Vec<String>,
+
+    /// Whether `<KEY>_FILE` is checked if the env variable is not set.
+    pub(crate) env_file_suffix: bool,
+
+    /// The name of the systemd credential to load this value from.
+    pub(crate) credential: Option<String>,
+    pub(crate) deserialize_with: Option<syn::Path>,
+    pub(crate) parse_env: Option<syn::Path>,
+
+    /// Function validating the value, called in `from_partial`.
+    pub(crate) validate: Option<syn::Path>,
+
+    /// Declarative constraints like `min`, checked in `from_partial`.
+    pub(crate) constraints: Vec<Constraint>,
+    pub(crate) kind: LeafKind,
+}
+
+pub(crate) enum Constraint {
+    Min(Expr),
+    Max(Expr),
+    OneOf(Vec<Expr>),
+    NonEmpty,
+}
+
 pub(crate) enum LeafKind {
     /// A non-optional leaf. `ty` is not `Option<_>`.
     Required {
@@ -74,6 +129,20 @@ pub(crate) enum Expr {
     Map(Vec<MapEntry>),
 }
 
+impl Expr {
+    /// Human readable representation of a scalar literal, used in error
+    /// messages. Strings are quoted.
+    pub(crate) fn display(&self) -> String {
+        match self {
+            Self::Str(lit) => format!("{:?}", lit.value()),
+            Self::Int(lit) => lit.base10_digits().to_owned(),
+            Self::Float(lit) => lit.base10_digits().to_owned(),
+            Self::Bool(lit) => lit.value.to_string(),
+            Self::Array(_) | Self::Map(_) => unreachable!("constraints only contain scalars"),
+        }
+    }
+}
+
 pub(crate) struct MapEntry {
     pub(crate) key: MapKey,
     pub(crate) value: Expr,
@@ -2,7 +2,7 @@ use proc_macro2::{TokenStream, Group, Delimiter, Ident};
 use syn::{Error, Token, parse::{Parse, ParseStream}, spanned::Spanned, punctuated::Punctuated};
 
 use crate::{
-    ir::{Input, Field, FieldKind, LeafKind, Expr, MapEntry, MapKey},
+    ir::{Input, Field, FieldKind, LeafField, LeafKind, Expr, MapEntry, MapKey, Constraint},
     util::{unwrap_option, is_option},
 };
 
@@ -18,34 +18,72 @@ impl Input {
         };
 
         let doc = extract_doc(&mut input.attrs);
-        let partial_attrs = extract_struct_attrs(input.attrs)?;
+        let derive_debug = input.attrs.iter()
+            .find_map(|attr| derive_debug_span(&attr.parse_meta().ok()?));
+        let attrs = extract_struct_attrs(input.attrs)?;
         let fields = fields.named.into_iter()
-            .map(Field::from_ast)
+            .map(|f| Field::from_ast(f, &attrs))
             .collect::<Result<Vec<_>, _>>()?;
 
+        for (i, field) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|f| f.key == field.key) {
+                return Err(Error::new(
+                    field.name.span(),
+                    format!("duplicate configuration key `{}` (after renaming)", field.key),
+                ));
+            }
+        }
+
+        // A derived `Debug` would print secret values. If `#[config(debug)]`
+        // is set, it would conflict with the generated impl anyway.
+        let has_secret = fields.iter().any(|f| f.secret);
+        if has_secret || attrs.debug {
+            let partial_derive_debug = attrs.partial_attrs.iter()
+                .find_map(|tokens| derive_debug_span(&syn::parse2(tokens.clone()).ok()?));
+            if let Some(span) = derive_debug.or(partial_derive_debug) {
+                return Err(Error::new(span, "`Debug` must not be derived for structs with \
+                    `#[config(secret)]` fields (or their partial types), as it would print \
+                    the secret values: use `#[config(debug)]` instead, which implements \
+                    `Debug` for both, printing \"<redacted>\" for secret fields"));
+            }
+        }
 
         Ok(Self {
             doc,
             visibility: input.vis,
-            partial_attrs,
+            partial_attrs: attrs.partial_attrs,
+            env_prefix: attrs.env_prefix,
+            debug: attrs.debug,
+            serialize: attrs.serialize,
+            validate: attrs.validate,
             name: input.ident,
             fields,
         })
     }
 }
 
-fn extract_struct_attrs(attrs: Vec<syn::Attribute>) -> Result<Vec<TokenStream>, Error> {
-    #[derive(Debug)]
+#[derive(Default)]
+struct StructAttrs {
+    partial_attrs: Vec<TokenStream>,
+    env_prefix: Option<String>,
+    debug: bool,
+    serialize: bool,
+    validate: Option<syn::Path>,
+    rename_all: Option<RenameRule>,
+}
+
+fn extract_struct_attrs(attrs: Vec<syn::Attribute>) -> Result<StructAttrs, Error> {
     enum StructAttr {
         InternalAttr(TokenStream),
+        EnvPrefix(String),
+        Debug,
+        Serialize,
+        Validate(syn::Path),
+        RenameAll(RenameRule),
     }
 
     impl Parse for StructAttr {
-        fn parse(input: ParseStream) -> syn::Result<Self> {
-            let content;
-            syn::parenthesized!(content in input);
-            assert_empty_or_comma(input)?;
-
+        fn parse(content: ParseStream) -> syn::Result<Self> {
             let name: Ident = content.parse()?;
             match &*name.to_string() {
                 "partial_attr" => {
@@ -54,33 +92,99 @@ fn extract_struct_attrs(attrs: Vec<syn::Attribute>) -> Result<Vec<TokenStream>,
                         return Err(Error::new_spanned(g,
                             "expected `(...)` but found different delimiter"));
                     }
-                    assert_empty_or_comma(&content)?;
+                    assert_empty_or_comma(content)?;
                     Ok(Self::InternalAttr(g.stream()))
                 }
+                "env_prefix" => {
+                    let _: Token![=] = content.parse()?;
+                    let prefix: syn::LitStr = content.parse()?;
+                    assert_empty_or_comma(content)?;
+                    parse_env_key(&prefix).map(Self::EnvPrefix)
+                }
+                "debug" => {
+                    assert_empty_or_comma(content)?;
+                    Ok(Self::Debug)
+                }
+                "serialize" => {
+                    assert_empty_or_comma(content)?;
+                    Ok(Self::Serialize)
+                }
+                "validate" => {
+                    let _: Token![=] = content.parse()?;
+                    let path: syn::Path = content.parse()?;
+                    assert_empty_or_comma(content)?;
+                    Ok(Self::Validate(path))
+                }
+                "rename_all" => {
+                    let _: Token![=] = content.parse()?;
+                    let rule: syn::LitStr = content.parse()?;
+                    assert_empty_or_comma(content)?;
+                    RenameRule::from_lit(&rule).map(Self::RenameAll)
+                }
                 _ => Err(Error::new_spanned(name, "unknown attribute")),
             }
         }
     }
 
-    let mut partial_attrs = Vec::new();
+    let mut out = StructAttrs::default();
     for attr in attrs {
         if !attr.path.is_ident("config") {
             continue;
         }
-        match syn::parse2::<StructAttr>(attr.tokens)? {
-            StructAttr::InternalAttr(tokens) => partial_attrs.push(tokens),
+        let span = attr.tokens.span();
+        type AttrList = Punctuated<StructAttr, Token![,]>;
+        for parsed in attr.parse_args_with(AttrList::parse_terminated)? {
+            match parsed {
+                StructAttr::InternalAttr(tokens) => out.partial_attrs.push(tokens),
+                StructAttr::EnvPrefix(prefix) => {
+                    if out.env_prefix.is_some() {
+                        return Err(Error::new(span, "duplicate 'env_prefix' confique attribute"));
+                    }
+                    out.env_prefix = Some(prefix);
+                }
+                StructAttr::Debug => {
+                    if out.debug {
+                        return Err(Error::new(span, "duplicate 'debug' confique attribute"));
+                    }
+                    out.debug = true;
+                }
+                StructAttr::Serialize => {
+                    if out.serialize {
+                        return Err(Error::new(span, "duplicate 'serialize' confique attribute"));
+                    }
+                    out.serialize = true;
+                }
+                StructAttr::Validate(path) => {
+                    if out.validate.is_some() {
+                        return Err(Error::new(span, "duplicate 'validate' confique attribute"));
+                    }
+                    out.validate = Some(path);
+                }
+                StructAttr::RenameAll(rule) => {
+                    if out.rename_all.is_some() {
+                        return Err(Error::new(span, "duplicate 'rename_all' confique attribute"));
+                    }
+                    out.rename_all = Some(rule);
+                }
+            }
         }
     }
 
-    Ok(partial_attrs)
+    Ok(out)
 }
 
 impl Field {
-    fn from_ast(mut field: syn::Field) -> Result<Self, Error> {
+    fn from_ast(mut field: syn::Field, struct_attrs: &StructAttrs) -> Result<Self, Error> {
         let doc = extract_doc(&mut field.attrs);
         let attrs = extract_internal_attrs(&mut field.attrs)?;
 
         let err = |msg| Err(Error::new(field.ident.span(), msg));
+        let name = field.ident.clone().expect("bug: expected named field");
+        let key = match (attrs.rename, &struct_attrs.rename_all) {
+            (Some(key), _) => key,
+            (None, Some(rule)) => rule.apply(&name.to_string()),
+            (None, None) => name.to_string(),
+        };
 
         // TODO: check no other attributes are here
         let kind = if attrs.nested {
@@ -97,11 +201,50 @@ impl Field {
                 return err("cannot specify `nested` and `deserialize_with` attributes \
                     at the same time");
             }
+            if attrs.credential.is_some() {
+                return err("cannot specify `nested` and `credential` attributes at the same time");
+            }
+            if attrs.env_file_suffix {
+                return err("cannot specify `nested` and `env_file_suffix` attributes \
+                    at the same time");
+            }
+            if attrs.secret {
+                return err("cannot specify `nested` and `secret` attributes at the same time");
+            }
+            if attrs.validate.is_some() {
+                return err("cannot specify `nested` and `validate` attributes at the same time \
+                    (use the `validate` attribute on the nested struct instead)");
+            }
+            if !attrs.constraints.is_empty() {
+                return err("constraint attributes (`min`, `max`, `one_of`, `non_empty`) \
+                    cannot be specified on nested fields");
+            }
+            if !attrs.env_aliases.is_empty() {
+                return err("cannot specify `nested` and `alias_env` attributes at the same time");
+            }
 
-            FieldKind::Nested { ty: field.ty }
+            FieldKind::Nested { ty: field.ty, env_prefix: attrs.env_prefix }
         } else {
-            if attrs.env.is_none() && attrs.parse_env.is_some() {
-                return err("cannot specify `parse_env` attribute without the `env` attribute");
+            if attrs.env_prefix.is_some() {
+                return err("`env_prefix` attribute can only be specified on nested fields \
+                    (or on the struct)");
+            }
+
+            if attrs.env.is_none() && struct_attrs.env_prefix.is_none()
+                && attrs.parse_env.is_some()
+            {
+                return err("cannot specify `parse_env` attribute without the `env` attribute \
+                    (or the `env_prefix` attribute on the struct)");
+            }
+            if attrs.env.is_none() && struct_attrs.env_prefix.is_none() && attrs.env_file_suffix {
+                return err("cannot specify `env_file_suffix` attribute without the `env` \
+                    attribute (or the `env_prefix` attribute on the struct)");
+            }
+            if attrs.env.is_none() && struct_attrs.env_prefix.is_none()
+                && !attrs.env_aliases.is_empty()
+            {
+                return err("cannot specify `alias_env` attribute without the `env` \
+                    attribute (or the `env_prefix` attribute on the struct)");
             }
 
             let kind = match unwrap_option(&field.ty) {
@@ -113,23 +256,34 @@ impl Field {
                 None => LeafKind::Required { default: attrs.default, ty: field.ty },
             };
 
-            FieldKind::Leaf {
+            // With `env_prefix` on the struct, fields without `env` are
+            // assigned a key at runtime, derived from the field name.
+            FieldKind::Leaf(Box::new(LeafField {
                 env: attrs.env,
+                env_aliases: attrs.env_aliases,
+                env_file_suffix: attrs.env_file_suffix,
+                credential: attrs.credential,
                 deserialize_with: attrs.deserialize_with,
                 parse_env: attrs.parse_env,
+                validate: attrs.validate,
+                constraints: attrs.constraints,
                 kind,
-            }
+            }))
         };
 
         Ok(Self {
             doc,
-            name: field.ident.expect("bug: expected named field"),
+            name,
+            key,
+            secret: attrs.secret,
+            aliases: attrs.aliases,
+            deprecated: attrs.deprecated,
             kind,
         })
     }
 
     pub(crate) fn is_leaf(&self) -> bool {
-        matches!(self.kind, FieldKind::Leaf { .. })
+        matches!(self.kind, FieldKind::Leaf(_))
     }
 }
 
@@ -191,6 +345,24 @@ impl Parse for MapKey {
     }
 }
 
+/// If `meta` is a `derive(...)` containing `Debug`, returns the span of the
+/// latter.
+fn derive_debug_span(meta: &syn::Meta) -> Option<proc_macro2::Span> {
+    match meta {
+        syn::Meta::List(list) if list.path.is_ident("derive") => {
+            list.nested.iter().find_map(|nested| match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(path))
+                    if path.segments.last().is_some_and(|s| s.ident == "Debug") =>
+                {
+                    Some(path.span())
+                }
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
 /// Extracts all doc string attributes from the list and return them as list of
 /// strings (in order).
 fn extract_doc(attrs: &mut Vec<syn::Attribute>) -> Vec<String> {
@@ -269,6 +441,22 @@ fn extract_internal_attrs(
                     duplicate_if!(out.env.is_some());
                     out.env = Some(key);
                 }
+                InternalAttr::EnvPrefix(prefix) => {
+                    duplicate_if!(out.env_prefix.is_some());
+                    out.env_prefix = Some(prefix);
+                }
+                InternalAttr::Secret => {
+                    duplicate_if!(out.secret);
+                    out.secret = true;
+                }
+                InternalAttr::EnvFileSuffix => {
+                    duplicate_if!(out.env_file_suffix);
+                    out.env_file_suffix = true;
+                }
+                InternalAttr::Credential(name) => {
+                    duplicate_if!(out.credential.is_some());
+                    out.credential = Some(name);
+                }
                 InternalAttr::ParseEnv(path) => {
                     duplicate_if!(out.parse_env.is_some());
                     out.parse_env = Some(path);
@@ -277,6 +465,32 @@ fn extract_internal_attrs(
                     duplicate_if!(out.deserialize_with.is_some());
                     out.deserialize_with = Some(path);
                 }
+                InternalAttr::Validate(path) => {
+                    duplicate_if!(out.validate.is_some());
+                    out.validate = Some(path);
+                }
+                InternalAttr::Alias(alias) => {
+                    duplicate_if!(out.aliases.contains(&alias));
+                    out.aliases.push(alias);
+                }
+                InternalAttr::AliasEnv(key) => {
+                    duplicate_if!(out.env_aliases.contains(&key));
+                    out.env_aliases.push(key);
+                }
+                InternalAttr::Deprecated(msg) => {
+                    duplicate_if!(out.deprecated.is_some());
+                    out.deprecated = Some(msg);
+                }
+                InternalAttr::Rename(key) => {
+                    duplicate_if!(out.rename.is_some());
+                    out.rename = Some(key);
+                }
+                InternalAttr::Constraint(constraint) => {
+                    duplicate_if!(out.constraints.iter().any(|c| {
+                        std::mem::discriminant(c) == std::mem::discriminant(&constraint)
+                    }));
+                    out.constraints.push(constraint);
+                }
             }
         }
     }
@@ -289,16 +503,36 @@ struct InternalAttrs {
     nested: bool,
     default: Option<Expr>,
     env: Option<String>,
+    env_prefix: Option<String>,
+    env_file_suffix: bool,
+    credential: Option<String>,
+    secret: bool,
     deserialize_with: Option<syn::Path>,
     parse_env: Option<syn::Path>,
+    validate: Option<syn::Path>,
+    constraints: Vec<Constraint>,
+    aliases: Vec<String>,
+    env_aliases: Vec<String>,
+    deprecated: Option<String>,
+    rename: Option<String>,
 }
 
 enum InternalAttr {
     Nested,
     Default(Expr),
     Env(String),
+    EnvPrefix(String),
+    EnvFileSuffix,
+    Credential(String),
+    Secret,
     DeserializeWith(syn::Path),
     ParseEnv(syn::Path),
+    Validate(syn::Path),
+    Constraint(Constraint),
+    Alias(String),
+    AliasEnv(String),
+    Deprecated(String),
+    Rename(String),
 }
 
 impl InternalAttr {
@@ -307,8 +541,21 @@ impl InternalAttr {
             Self::Nested => "nested",
             Self::Default(_) => "default",
             Self::Env(_) => "env",
+            Self::EnvPrefix(_) => "env_prefix",
+            Self::EnvFileSuffix => "env_file_suffix",
+            Self::Credential(_) => "credential",
+            Self::Secret => "secret",
             Self::ParseEnv(_) => "parse_env",
             Self::DeserializeWith(_) => "deserialize_with",
+            Self::Validate(_) => "validate",
+            Self::Alias(_) => "alias",
+            Self::AliasEnv(_) => "alias_env",
+            Self::Deprecated(_) => "deprecated",
+            Self::Rename(_) => "rename",
+            Self::Constraint(Constraint::Min(_)) => "min",
+            Self::Constraint(Constraint::Max(_)) => "max",
+            Self::Constraint(Constraint::OneOf(_)) => "one_of",
+            Self::Constraint(Constraint::NonEmpty) => "non_empty",
         }
     }
 }
@@ -333,15 +580,38 @@ impl Parse for InternalAttr {
                 let _: Token![=] = input.parse()?;
                 let key: syn::LitStr = input.parse()?;
                 assert_empty_or_comma(input)?;
-                let value = key.value();
-                if value.contains('=') || value.contains('\0') {
-                    Err(syn::Error::new(
-                        key.span(),
-                        "environment variable key must not contain '=' or null bytes",
-                    ))
-                } else {
-                    Ok(Self::Env(value))
+                parse_env_key(&key).map(Self::Env)
+            }
+
+            "env_prefix" => {
+                let _: Token![=] = input.parse()?;
+                let prefix: syn::LitStr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                parse_env_key(&prefix).map(Self::EnvPrefix)
+            }
+
+            "secret" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::Secret)
+            }
+
+            "env_file_suffix" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::EnvFileSuffix)
+            }
+
+            "credential" => {
+                let _: Token![=] = input.parse()?;
+                let name: syn::LitStr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                let value = name.value();
+                if value.is_empty() || value.contains('/') || value.contains('\0') {
+                    return Err(Error::new(
+                        name.span(),
+                        "credential name must be non-empty and must not contain '/' or null bytes",
+                    ));
                 }
+                Ok(Self::Credential(value))
             }
 
             "parse_env" => {
@@ -360,11 +630,175 @@ impl Parse for InternalAttr {
                 Ok(Self::DeserializeWith(path))
             }
 
+            "validate" => {
+                let _: Token![=] = input.parse()?;
+                let path: syn::Path = input.parse()?;
+                assert_empty_or_comma(input)?;
+
+                Ok(Self::Validate(path))
+            }
+
+            "min" | "max" => {
+                let _: Token![=] = input.parse()?;
+                let span = input.span();
+                let expr: Expr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                if !matches!(expr, Expr::Int(_) | Expr::Float(_)) {
+                    return Err(Error::new(span, "expected integer or float literal"));
+                }
+
+                match &*ident.to_string() {
+                    "min" => Ok(Self::Constraint(Constraint::Min(expr))),
+                    _ => Ok(Self::Constraint(Constraint::Max(expr))),
+                }
+            }
+
+            "one_of" => {
+                let _: Token![=] = input.parse()?;
+                let span = input.span();
+                let expr: Expr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                match expr {
+                    Expr::Array(items) if !items.is_empty() && items.iter().all(|item| {
+                        matches!(item, Expr::Str(_) | Expr::Int(_) | Expr::Float(_) | Expr::Bool(_))
+                    }) => Ok(Self::Constraint(Constraint::OneOf(items))),
+                    _ => Err(Error::new(span, "expected non-empty array of literals")),
+                }
+            }
+
+            "non_empty" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::Constraint(Constraint::NonEmpty))
+            }
+
+            "alias" => {
+                let _: Token![=] = input.parse()?;
+                let name: syn::LitStr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                let value = name.value();
+                if value.is_empty() || value.contains('.') {
+                    return Err(Error::new(
+                        name.span(),
+                        "alias must be non-empty and must not contain '.'",
+                    ));
+                }
+                Ok(Self::Alias(value))
+            }
+
+            "alias_env" => {
+                let _: Token![=] = input.parse()?;
+                let key: syn::LitStr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                parse_env_key(&key).map(Self::AliasEnv)
+            }
+
+            "deprecated" => {
+                let _: Token![=] = input.parse()?;
+                let msg: syn::LitStr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                Ok(Self::Deprecated(msg.value()))
+            }
+
+            "rename" => {
+                let _: Token![=] = input.parse()?;
+                let name: syn::LitStr = input.parse()?;
+                assert_empty_or_comma(input)?;
+                let value = name.value();
+                if value.is_empty() || value.contains('.') {
+                    return Err(Error::new(
+                        name.span(),
+                        "renamed key must be non-empty and must not contain '.'",
+                    ));
+                }
+                Ok(Self::Rename(value))
+            }
+
             _ => Err(syn::Error::new(ident.span(), "unknown confique attribute")),
         }
     }
 }
 
+/// Returns the value of the given literal, making sure it can be used as (part
+/// of) an environment variable key.
+fn parse_env_key(lit: &syn::LitStr) -> Result<String, Error> {
+    let value = lit.value();
+    if value.contains('=') || value.contains('\0') {
+        Err(Error::new(
+            lit.span(),
+            "environment variable key must not contain '=' or null bytes",
+        ))
+    } else {
+        Ok(value)
+    }
+}
+
+/// A case convention for `#[config(rename_all = "...")]`. Same as serde's.
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &syn::LitStr) -> Result<Self, Error> {
+        match &*lit.value() {
+            "lowercase" => Ok(Self::Lower),
+            "UPPERCASE" => Ok(Self::Upper),
+            "PascalCase" => Ok(Self::Pascal),
+            "camelCase" => Ok(Self::Camel),
+            "snake_case" => Ok(Self::Snake),
+            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
+            "kebab-case" => Ok(Self::Kebab),
+            "SCREAMING-KEBAB-CASE" => Ok(Self::ScreamingKebab),
+            _ => Err(Error::new(
+                lit.span(),
+                "unknown case convention, expected one of: \"lowercase\", \"UPPERCASE\", \
+                    \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \
+                    \"kebab-case\", \"SCREAMING-KEBAB-CASE\"",
+            )),
+        }
+    }
+
+    /// Applies this rule to a field name, which is assumed to be snake case.
+    /// Matches what serde does for `rename_all` on structs.
+    fn apply(&self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut out = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        out.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        out.push(c);
+                    }
+                }
+                out
+            }
+            Self::Camel => {
+                let pascal = Self::Pascal.apply(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
 fn assert_empty_or_comma(input: ParseStream) -> Result<(), Error> {
     if input.is_empty() || input.peek(Token![,]) {
         Ok(())
@@ -0,0 +1,178 @@
+//! Loading configuration values from command line arguments.
+
+use std::{collections::HashMap, ffi::OsString, fmt::Write, path::Path};
+
+use crate::{
+    dotted,
+    error::{redact_msg, ErrorInner},
+    meta::{Expr, FieldKind, LeafKind, Type},
+    Config, Error, Partial,
+};
+
+
+/// Parses the given command line arguments into a partial configuration. The
+/// first item is the program name and is ignored. Every other argument has to
+/// be `--path=value` or `--path value`, where `path` is the dotted path of a
+/// leaf field. If the value of a `bool` field is omitted (i.e. the argument
+/// is followed by another `--` argument or is the last one), `true` is used.
+/// Values are deserialized like environment variables. If the same value is
+/// specified multiple times, the last one wins.
+///
+/// Also returns a map from path to the argument (as given, e.g. `--log.level`)
+/// that set the value.
+pub(crate) fn load<C: Config>(
+    args: Vec<OsString>,
+) -> Result<(C::Partial, HashMap<String, String>), Error> {
+    let leaves = dotted::leaves(&C::META);
+
+    let mut args = args.into_iter();
+    let program = args.next();
+    let mut args = args.peekable();
+    let mut partial = C::Partial::empty();
+    let mut origins = HashMap::new();
+    while let Some(arg) = args.next() {
+        let arg = arg.into_string()
+            .map_err(|arg| invalid(&arg.to_string_lossy(), "not valid unicode"))?;
+        if arg == "-h" || arg == "--help" {
+            return Err(ErrorInner::HelpRequested {
+                help: help::<C>(program.as_deref().map(Path::new)),
+            }.into());
+        }
+
+        let Some(name_value) = arg.strip_prefix("--") else {
+            return Err(invalid(&arg, "expected an option starting with `--`"));
+        };
+        let (name, value) = match name_value.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_owned())),
+            None => {
+                let has_value = args.peek()
+                    .is_some_and(|next| !next.to_string_lossy().starts_with("--"));
+                let value = if has_value {
+                    let next = args.next().unwrap();
+                    let next = next.into_string()
+                        .map_err(|_| invalid(&arg, "value is not valid unicode"))?;
+                    Some(next)
+                } else {
+                    None
+                };
+                (name_value, value)
+            }
+        };
+
+        let leaf = dotted::find_leaf(&leaves, name).ok_or_else(|| {
+            let msg = match dotted::suggestion(dotted::paths(&leaves), name) {
+                Some(path) => format!("unknown configuration value (did you mean `--{path}`?)"),
+                None => "unknown configuration value".into(),
+            };
+            invalid(&arg, &msg)
+        })?;
+
+        let value = match value {
+            Some(value) => value,
+            None if leaf.ty == Type::Bool => "true".into(),
+            None => return Err(invalid(&format!("--{name}"), "missing value")),
+        };
+        let layer = dotted::partial_from_path::<C::Partial>(&leaf.path, value)
+            .map_err(|e| -> Error {
+                ErrorInner::ArgDeserialization {
+                    field: leaf.path.clone(),
+                    arg: format!("--{name}"),
+                    msg: redact_msg(e.0, leaf.secret),
+                }.into()
+            })?;
+        partial = layer.with_fallback(partial);
+        origins.insert(leaf.path.clone(), format!("--{name}"));
+    }
+
+    Ok((partial, origins))
+}
+
+fn invalid(arg: &str, msg: &str) -> Error {
+    ErrorInner::InvalidArg { arg: arg.to_owned(), msg: msg.to_owned() }.into()
+}
+
+/// Renders the `--help` output, listing all leaf fields with their docs, env
+/// key and default value.
+fn help<C: Config>(program: Option<&Path>) -> String {
+    let program = program
+        .and_then(|p| p.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "app".into());
+
+    let mut out = String::new();
+    for line in C::META.doc {
+        out.push_str(line.trim());
+        out.push('\n');
+    }
+    if !C::META.doc.is_empty() {
+        out.push('\n');
+    }
+    writeln!(out, "Usage: {program} [OPTIONS]\n\nOptions:").unwrap();
+
+    C::META.for_each_leaf(|path, field, scope| {
+        writeln!(out, "  --{path} <VALUE>").unwrap();
+        for line in field.doc {
+            writeln!(out, "      {}", line.trim()).unwrap();
+        }
+
+        let mut notes = Vec::new();
+        if let Some(key) = field.env_key(scope) {
+            notes.push(format!("[env: {key}]"));
+        }
+        if let FieldKind::Leaf { credential: Some(name), .. } = field.kind {
+            notes.push(format!("[credential: {name}]"));
+        }
+        match field.kind {
+            FieldKind::Leaf { kind: LeafKind::Required { default: Some(expr) }, .. } => {
+                notes.push(format!("[default: {}]", DisplayExpr(&expr)));
+            }
+            FieldKind::Leaf { kind: LeafKind::Required { default: None }, .. } => {
+                notes.push("[required]".into());
+            }
+            _ => {}
+        }
+        if !notes.is_empty() {
+            writeln!(out, "      {}", notes.join(" ")).unwrap();
+        }
+        out.push('\n');
+    });
+
+    out.push_str("  -h, --help\n      Print this help and exit.\n");
+    out
+}
+
+/// Human readable, format agnostic representation of default values.
+struct DisplayExpr<'a>(&'a Expr);
+
+impl std::fmt::Display for DisplayExpr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.0 {
+            Expr::Str(s) => f.write_str(s),
+            Expr::Float(v) => v.fmt(f),
+            Expr::Integer(v) => v.fmt(f),
+            Expr::Bool(v) => v.fmt(f),
+            Expr::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    DisplayExpr(item).fmt(f)?;
+                }
+                f.write_str("]")
+            }
+            Expr::Map(entries) => {
+                f.write_str("{")?;
+                for (i, entry) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    DisplayExpr(&Expr::from(entry.key)).fmt(f)?;
+                    f.write_str(": ")?;
+                    DisplayExpr(&entry.value).fmt(f)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
@@ -1,10 +1,27 @@
-#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
-use std::path::PathBuf;
+use std::{
+    collections::{HashMap, HashSet},
+    env::VarError,
+    ffi::OsString,
+    path::PathBuf,
+};
 
-use crate::{Config, Error, Partial};
+use crate::{
+    args,
+    credentials,
+    dotted,
+    env::{dotenv, EnvSource, ProcessEnv},
+    key_dir,
+    overrides,
+    internal::{env_var_with_aliases, EnvScope},
+    meta::{Field, FieldKind, Meta},
+    provenance::{Provenance, ValueSource},
+    Config, Error, Partial, SourceLayer, Warning,
+};
 
 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
-use crate::File;
+use std::path::Path;
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+use crate::{file::files_in_dir, File};
 
 
 
@@ -15,11 +32,13 @@ use crate::File;
 /// [`Config::builder`].
 pub struct Builder<C: Config> {
     sources: Vec<Source<C>>,
+    env_file_suffix: bool,
+    strict: bool,
 }
 
 impl<C: Config> Builder<C> {
     pub(crate) fn new() -> Self {
-        Self { sources: vec![] }
+        Self { sources: vec![], env_file_suffix: false, strict: false }
     }
 
     /// Adds a configuration file as source. Infers the format from the file
@@ -34,12 +53,251 @@ impl<C: Config> Builder<C> {
         self
     }
 
+    /// Adds all configuration files in the directory `path` as sources, like
+    /// a `conf.d` drop-in directory. Only files with a file extension of a
+    /// supported format (see [`FileFormat`][crate::FileFormat]) are loaded;
+    /// other files, hidden files (starting with `.`) and subdirectories are
+    /// ignored.
+    ///
+    /// Each file is its own layer. Files are ordered lexically by name, with
+    /// **later files having a higher priority**, so `20-local.toml` overrides
+    /// values from `10-defaults.toml`. All files together have the priority
+    /// of this `dir` call relative to the other sources of this builder.
+    ///
+    /// The directory is not considered required: if it does not exist, no
+    /// layers are added.
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    pub fn dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::Dir(path.into()));
+        self
+    }
+
     /// Adds the environment variables as a source.
     pub fn env(mut self) -> Self {
         self.sources.push(Source::Env);
         self
     }
 
+    /// Adds the given variables as a source, loaded exactly like
+    /// [`Builder::env`] loads variables from the environment. Useful in tests,
+    /// as the process environment does not need to be modified.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(env = "APP_PORT")]
+    ///     port: u16,
+    /// }
+    ///
+    /// let conf = Conf::builder()
+    ///     .env_from([("APP_PORT".to_string(), "8080".to_string())])
+    ///     .load()?;
+    /// assert_eq!(conf.port, 8080);
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    pub fn env_from(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.sources.push(Source::EnvFrom(vars.into_iter().collect()));
+        self
+    }
+
+    /// Adds a dotenv-style file (e.g. `.env`) as source. The variables
+    /// defined in that file are loaded exactly like [`Builder::env`] loads
+    /// variables from the environment, but without reading or modifying the
+    /// environment of the process.
+    ///
+    /// The file contains `KEY=VALUE` lines, optionally prefixed with
+    /// `export`. Values can be single or double quoted (with the latter
+    /// supporting escape sequences like `\n`) and lines starting with `#` are
+    /// comments. Variable expansion is not supported.
+    ///
+    /// The file is not considered required: if the file does not exist, an
+    /// empty configuration (`C::Partial::empty()`) is used for this layer.
+    pub fn env_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::EnvFile(path.into()));
+        self
+    }
+
+    /// Enables the `_FILE` suffix indirection for all fields loaded from
+    /// environment variables (by [`Builder::env`], [`Builder::env_from`] and
+    /// [`Builder::env_file`]), as if all of them had the
+    /// `#[config(env_file_suffix)]` attribute: if the variable `KEY` is not
+    /// set, but `KEY_FILE` is, the value is read from the file at the path
+    /// specified in `KEY_FILE`. This is a common way of passing secrets in
+    /// Docker and other container orchestrators.
+    ///
+    /// Can be called at any point, it affects all sources of this builder.
+    pub fn env_file_suffix(mut self) -> Self {
+        self.env_file_suffix = true;
+        self
+    }
+
+    /// Enables strict mode: keys in configuration files (see [`File::strict`])
+    /// and files in key directories that do not correspond to any
+    /// configuration value are reported as errors, suggesting a similarly
+    /// named value if there is one. By default, they are silently ignored.
+    /// Like for [`File::strict`], serde attributes changing the keys (added via
+    /// `partial_attr`) are not supported.
+    ///
+    /// ```
+    /// use confique::{Config, ErrorKind};
+    ///
+    /// #[derive(Debug, Config)]
+    /// struct Conf {
+    ///     #[config(default = false)]
+    ///     stdout: bool,
+    /// }
+    ///
+    /// # #[cfg(feature = "toml")] {
+    /// # let dir = std::env::temp_dir().join("confique-doctest-strict");
+    /// # std::fs::create_dir_all(&dir).unwrap();
+    /// # let path = dir.join("app.toml");
+    /// std::fs::write(&path, "stdot = true").unwrap();
+    /// let err = Conf::builder().file(&path).strict().load().unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::UnknownKey);
+    /// assert!(err.to_string().contains("did you mean `stdout`?"));
+    /// # }
+    /// ```
+    ///
+    /// Can be called at any point, it affects all sources of this builder.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Adds a directory containing one file per configuration value as
+    /// source. This is the layout of mounted Kubernetes ConfigMaps and
+    /// secrets, for example.
+    ///
+    /// The file name is the dotted path of the field, e.g. `log.file`. As
+    /// dots are not allowed in some key names, `log__file` is accepted as
+    /// well. Files not corresponding to any value (unless in strict mode, see
+    /// [`Builder::strict`]), hidden files (starting with `.`) and
+    /// subdirectories are ignored. Trailing newlines are removed from
+    /// the file contents, which are then deserialized exactly like
+    /// environment variables.
+    ///
+    /// The directory is not considered required: if it does not exist, an
+    /// empty configuration (`C::Partial::empty()`) is used for this layer.
+    pub fn key_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::KeyDir(path.into()));
+        self
+    }
+
+    /// Adds systemd credentials as a source. Values of fields with the
+    /// `#[config(credential = "name")]` attribute are loaded from the file
+    /// `name` in the directory specified by the `CREDENTIALS_DIRECTORY`
+    /// environment variable, which systemd sets for services using
+    /// `LoadCredential=` and similar settings.
+    ///
+    /// If that variable is not set or a credential file does not exist, the
+    /// corresponding values are not set by this layer. Trailing newlines are
+    /// removed from the file contents, which are then deserialized exactly
+    /// like environment variables.
+    pub fn systemd_credentials(mut self) -> Self {
+        self.sources.push(Source::Credentials(None));
+        self
+    }
+
+    /// Like [`Builder::systemd_credentials`], but loads the credentials from
+    /// the given directory instead of the one specified by
+    /// `CREDENTIALS_DIRECTORY`. Useful for testing.
+    pub fn systemd_credentials_from(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::Credentials(Some(dir.into())));
+        self
+    }
+
+    /// Adds command line arguments as a source, usually
+    /// `std::env::args_os()`. The first item is the program name and is
+    /// ignored.
+    ///
+    /// Every configuration value can be set with `--path=value` or
+    /// `--path value`, where `path` is the dotted path of the field (e.g.
+    /// `--http.port 8080`). Dashes in the path can be used instead of
+    /// underscores. If the value of a `bool` field is omitted, `true` is used,
+    /// so `--log.stdout` is the same as `--log.stdout=true`. Values are
+    /// deserialized exactly like environment variables, so for example `yes`
+    /// and `1` are valid booleans. If a value is specified multiple times, the
+    /// last one wins.
+    ///
+    /// If `--help` or `-h` is passed, [`Builder::load`] returns an error for
+    /// which [`Error::is_help_request`] returns `true` and whose `Display`
+    /// output lists all configuration values with their docs.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    ///     verbose: Option<bool>,
+    /// }
+    ///
+    /// let conf = Conf::builder()
+    ///     .args(["app", "--port", "80", "--verbose"])
+    ///     .load()?;
+    /// assert_eq!(conf.port, 80);
+    /// assert_eq!(conf.verbose, Some(true));
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString>,
+    {
+        self.sources.push(Source::Args(args.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Adds a list of `path=value` strings as a source, similar to `git -c`.
+    /// This is useful to set single values from the command line, e.g. via an
+    /// `-o` flag of your application.
+    ///
+    /// `path` is the dotted path of a field, e.g. `http.port` (dashes can be
+    /// used instead of underscores). Values are deserialized exactly like
+    /// environment variables. If a value is specified multiple times, the
+    /// last one wins. [`Builder::load`] returns an error if a string does not
+    /// contain `=` or if the path does not refer to an existing value.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Debug, Config)]
+    /// struct Conf {
+    ///     #[config(nested)]
+    ///     log: LogConf,
+    /// }
+    ///
+    /// #[derive(Debug, Config)]
+    /// struct LogConf {
+    ///     #[config(default = "info")]
+    ///     level: String,
+    /// }
+    ///
+    /// fn main() -> Result<(), confique::Error> {
+    ///     let conf = Conf::builder().overrides(["log.level=debug"]).load()?;
+    ///     assert_eq!(conf.log.level, "debug");
+    ///
+    ///     let err = Conf::builder().overrides(["log.levle=debug"]).load().unwrap_err();
+    ///     assert_eq!(
+    ///         err.to_string(),
+    ///         "invalid configuration override `log.levle=debug`: unknown \
+    ///             configuration value `log.levle` (did you mean `log.level`?)",
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn overrides<I>(mut self, overrides: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.sources.push(Source::Overrides(overrides.into_iter().map(Into::into).collect()));
+        self
+    }
+
     /// Adds an already loaded partial configuration as source.
     pub fn preloaded(mut self, partial: C::Partial) -> Self {
         self.sources.push(Source::Preloaded(partial));
@@ -50,27 +308,513 @@ impl<C: Config> Builder<C> {
     /// priority, later sources only fill potential gaps.
     ///
     /// Will return an error if loading the sources fails or if the merged
-    /// configuration does not specify all required values.
+    /// configuration does not specify all required values. Loading does not
+    /// stop at the first error: all sources are loaded and the returned error
+    /// contains all problems (see [`Error::errors`]). If a source failed to
+    /// load, missing values are not reported, as that source might have
+    /// provided them.
     pub fn load(self) -> Result<C, Error> {
+        self.load_impl(false).map(|(config, _)| config)
+    }
+
+    /// Like [`Builder::load`], but additionally returns [`Warning`]s about
+    /// problems that did not prevent loading, like unknown keys in files (if
+    /// not in strict mode), environment variables with the env prefix of `C`
+    /// that do not correspond to any value, or missing optional files.
+    ///
+    /// ```
+    /// use confique::{Config, ValueSource, Warning};
+    ///
+    /// #[derive(Config)]
+    /// #[config(env_prefix = "APP_")]
+    /// struct Conf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    /// }
+    ///
+    /// let vars = [("APP_PROT".to_string(), "80".to_string())];
+    /// let (conf, warnings) = Conf::builder().env_from(vars).load_with_warnings()?;
+    /// assert_eq!(conf.port, 8080);
+    /// assert_eq!(warnings, [Warning::UnknownEnvVar {
+    ///     source: ValueSource::Env { key: "APP_PROT".into() },
+    ///     suggestion: Some("APP_PORT".into()),
+    /// }]);
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    pub fn load_with_warnings(self) -> Result<(C, Vec<Warning>), Error> {
+        self.load_impl(true)
+    }
+
+    /// Loads and merges all sources. Warnings are only collected if
+    /// `collect_warnings` is set, as checking for unknown keys in files
+    /// requires parsing them a second time.
+    fn load_impl(self, collect_warnings: bool) -> Result<(C, Vec<Warning>), Error> {
+        let Loaded { layers, errors, warnings } = self.load_layers(collect_warnings)?;
+        let mut partial = C::Partial::empty();
+        for layer in layers {
+            partial = partial.with_fallback(layer.partial);
+        }
+
+        let config = combine_errors(
+            errors,
+            C::from_partial(partial.with_fallback(C::Partial::default_values())),
+        )?;
+        Ok((config, warnings))
+    }
+
+    /// Like [`Builder::load`], but additionally returns a [`Provenance`]
+    /// describing which source supplied each value of the configuration.
+    /// To print the configuration annotated with this information, see
+    /// `toml::to_string_with_provenance` and the YAML/JSON5 equivalents.
+    ///
+    /// ```
+    /// use confique::{Config, ValueSource};
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    /// }
+    ///
+    /// let (conf, provenance) = Conf::builder().load_with_provenance()?;
+    /// assert_eq!(conf.port, 8080);
+    /// assert_eq!(provenance.get("port"), Some(&ValueSource::Default));
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    pub fn load_with_provenance(self) -> Result<(C, Provenance), Error> {
+        let Loaded { mut layers, errors, .. } = self.load_layers(false)?;
+        layers.push(Layer {
+            partial: C::Partial::default_values(),
+            origin: Origin::Default,
+        });
+
+        let set_paths = layers.iter()
+            .map(|layer| layer.partial.set_paths().into_iter().collect::<HashSet<_>>())
+            .collect::<Vec<_>>();
+        let mut entries = Vec::new();
+        C::META.for_each_leaf(|path, field, scope| {
+            if let Some(i) = set_paths.iter().position(|set| set.contains(path)) {
+                let source = layers[i].origin.value_source(path, field, scope);
+                entries.push((path.to_owned(), source));
+            }
+        });
+
         let mut partial = C::Partial::empty();
+        for layer in layers {
+            partial = partial.with_fallback(layer.partial);
+        }
+
+        let config = combine_errors(errors, C::from_partial(partial))?;
+        Ok((config, Provenance::new(entries)))
+    }
+
+    /// Loads all sources into separate layers, without merging them. The
+    /// default values are not included. Errors of individual sources are
+    /// collected so that they can be reported together with the errors of
+    /// `from_partial`. Only a help request (`--help`) is returned immediately.
+    /// Warnings are returned only if `collect_warnings` is set.
+    fn load_layers(self, collect_warnings: bool) -> Result<Loaded<C>, Error> {
+        let mut env_scope = EnvScope::root(C::META.env_prefix);
+        if self.env_file_suffix {
+            env_scope = env_scope.with_file_suffix();
+        }
+
+        let mut ctx = LoadContext {
+            env_scope,
+            strict: self.strict,
+            preloaded_count: 0,
+            collect_warnings,
+            warnings: Vec::new(),
+        };
+        let mut layers = Vec::new();
+        let mut errors = Vec::new();
         for source in self.sources {
-            let layer = match source {
-                #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
-                Source::File(path) => File::new(path)?.load()?,
-                Source::Env => C::Partial::from_env()?,
-                Source::Preloaded(p) => p,
+            let layer = source.layer();
+            match load_source::<C>(source, &mut ctx) {
+                Ok(new) => layers.extend(new),
+                Err(e) if e.is_help_request() => return Err(e),
+                Err(e) => errors.push(match layer {
+                    Some(layer) => e.with_layer(layer),
+                    None => e,
+                }),
+            }
+        }
+
+        warn_deprecated(&layers, &mut ctx.warnings);
+        Ok(Loaded { layers, errors, warnings: ctx.warnings })
+    }
+}
+
+/// The result of loading all sources of a builder, see `load_layers`.
+struct Loaded<C: Config> {
+    layers: Vec<Layer<C>>,
+    errors: Vec<Error>,
+    warnings: Vec<Warning>,
+}
+
+/// Combines the errors that occured while loading the sources with the result
+/// of `from_partial`. If any source failed to load, missing values are not
+/// reported, as the failed source might have provided them.
+fn combine_errors<C>(mut errors: Vec<Error>, res: Result<C, Error>) -> Result<C, Error> {
+    if errors.is_empty() {
+        return res;
+    }
+    if let Err(e) = res {
+        errors.extend(e.into_errors().into_iter().filter(|e| !e.is_missing_value()));
+    }
+    Err(Error::combine(errors).expect("at least one error"))
+}
+
+/// State shared by loading all sources of a builder.
+struct LoadContext {
+    env_scope: EnvScope,
+    strict: bool,
+    preloaded_count: usize,
+
+    /// Whether warnings are requested. If not, expensive checks (like parsing
+    /// files a second time for unknown keys) are skipped, while cheap ones
+    /// might still add to `warnings`.
+    collect_warnings: bool,
+    warnings: Vec<Warning>,
+}
+
+impl LoadContext {
+    /// Loads the file at `path`, checking for unknown keys only if required.
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    fn load_file<P: Partial>(&mut self, path: &Path) -> Result<P, Error> {
+        let file = File::new(path)?;
+        let file = if self.strict { file.strict() } else { file };
+        if self.collect_warnings {
+            file.load_with_warnings(&mut self.warnings)
+        } else {
+            file.load()
+        }
+    }
+}
+
+/// Loads a single source into (usually) one layer.
+fn load_source<C: Config>(
+    source: Source<C>,
+    ctx: &mut LoadContext,
+) -> Result<Vec<Layer<C>>, Error> {
+    let layer = match source {
+        #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+        Source::Dir(dir) => {
+            let Some(files) = files_in_dir(&dir)? else {
+                ctx.warnings.push(Warning::MissingFile { path: dir });
+                return Ok(vec![]);
             };
 
-            partial = partial.with_fallback(layer);
+            // Later files have a higher priority, so they come first.
+            return files.into_iter().rev()
+                .map(|path| Ok(Layer {
+                    partial: ctx.load_file(&path)?,
+                    origin: Origin::File(path),
+                }))
+                .collect();
+        }
+        #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+        Source::File(path) => Layer {
+            partial: ctx.load_file(&path)?,
+            origin: Origin::File(path),
+        },
+        Source::Env => {
+            if ctx.collect_warnings {
+                warn_env_vars::<C>(&ProcessEnv, &mut ctx.warnings, |key| {
+                    ValueSource::Env { key }
+                });
+            }
+            Layer {
+                partial: C::Partial::from_env_scoped(&ProcessEnv, &ctx.env_scope)?,
+                origin: Origin::Env(env_keys_read::<C>(&ProcessEnv, &ctx.env_scope)),
+            }
+        }
+        Source::EnvFrom(vars) => {
+            if ctx.collect_warnings {
+                warn_env_vars::<C>(&vars, &mut ctx.warnings, |key| ValueSource::Env { key });
+            }
+            Layer {
+                partial: C::Partial::from_env_scoped(&vars, &ctx.env_scope)?,
+                origin: Origin::Env(env_keys_read::<C>(&vars, &ctx.env_scope)),
+            }
+        }
+        Source::EnvFile(path) => {
+            let vars = dotenv::load(&path)?;
+            if ctx.collect_warnings {
+                warn_env_vars::<C>(&vars, &mut ctx.warnings, |key| ValueSource::EnvFile {
+                    path: path.clone(),
+                    key,
+                });
+            }
+            Layer {
+                partial: C::Partial::from_env_scoped(&vars, &ctx.env_scope)?,
+                origin: Origin::EnvFile(path, env_keys_read::<C>(&vars, &ctx.env_scope)),
+            }
+        }
+        Source::KeyDir(dir) => {
+            let (partial, origins) = key_dir::load::<C>(&dir, ctx.strict, &mut ctx.warnings)?;
+            Layer { partial, origin: Origin::KeyDir(origins) }
+        }
+        Source::Credentials(dir) => {
+            let Some(dir) = dir.or_else(|| {
+                std::env::var_os(credentials::DIR_ENV_KEY).map(PathBuf::from)
+            }) else {
+                return Ok(vec![]);
+            };
+            Layer {
+                partial: credentials::load::<C>(&dir)?,
+                origin: Origin::Credentials,
+            }
+        }
+        Source::Args(args) => {
+            let (partial, origins) = args::load::<C>(args)?;
+            Layer { partial, origin: Origin::Args(origins) }
+        }
+        Source::Overrides(overrides) => {
+            let (partial, origins) = overrides::load::<C>(overrides)?;
+            Layer { partial, origin: Origin::Overrides(origins) }
         }
+        Source::Preloaded(partial) => {
+            ctx.preloaded_count += 1;
+            Layer {
+                partial,
+                origin: Origin::Preloaded(ctx.preloaded_count - 1),
+            }
+        }
+    };
 
-        C::from_partial(partial.with_fallback(C::Partial::default_values()))
-    }
+    Ok(vec![layer])
 }
 
 enum Source<C: Config> {
     #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
     File(PathBuf),
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    Dir(PathBuf),
     Env,
+    EnvFrom(HashMap<String, String>),
+    EnvFile(PathBuf),
+    KeyDir(PathBuf),
+
+    /// `None` means the directory in `CREDENTIALS_DIRECTORY`.
+    Credentials(Option<PathBuf>),
+    Args(Vec<OsString>),
+    Overrides(Vec<String>),
     Preloaded(C::Partial),
 }
+
+impl<C: Config> Source<C> {
+    /// The layer kind reported in errors of this source.
+    fn layer(&self) -> Option<SourceLayer> {
+        match self {
+            #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+            Self::File(_) | Self::Dir(_) => Some(SourceLayer::File),
+            Self::Env | Self::EnvFrom(_) => Some(SourceLayer::Env),
+            Self::EnvFile(_) => Some(SourceLayer::EnvFile),
+            Self::KeyDir(_) => Some(SourceLayer::KeyDir),
+            Self::Credentials(_) => Some(SourceLayer::Credentials),
+            Self::Args(_) => Some(SourceLayer::Args),
+            Self::Overrides(_) => Some(SourceLayer::Overrides),
+            Self::Preloaded(_) => None,
+        }
+    }
+}
+
+/// A loaded source.
+struct Layer<C: Config> {
+    partial: C::Partial,
+    origin: Origin,
+}
+
+/// Where a layer was loaded from.
+enum Origin {
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    File(PathBuf),
+
+    /// Maps from path to the env key it was actually read from.
+    Env(HashMap<String, String>),
+
+    /// The path of the dotenv file, and the keys like in `Env`.
+    EnvFile(PathBuf, HashMap<String, String>),
+
+    /// Maps from path to the file that set it.
+    KeyDir(HashMap<String, PathBuf>),
+    Credentials,
+
+    /// Maps from path to the argument that set it, as given.
+    Args(HashMap<String, String>),
+
+    /// Maps from path to the override string that set it.
+    Overrides(HashMap<String, String>),
+    Preloaded(usize),
+    Default,
+}
+
+impl Origin {
+    /// Returns the source of the value of `field` (at `path`) in a layer with
+    /// this origin.
+    fn value_source(&self, path: &str, field: &Field, scope: &EnvScope) -> ValueSource {
+        match self {
+            #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+            Self::File(path) => ValueSource::File(path.clone()),
+            Self::Env(keys) => ValueSource::Env { key: env_key(keys, path, field, scope) },
+            Self::EnvFile(file, keys) => ValueSource::EnvFile {
+                path: file.clone(),
+                key: env_key(keys, path, field, scope),
+            },
+            Self::KeyDir(origins) => ValueSource::KeyFile(
+                origins.get(path).cloned().unwrap_or_else(|| missing_origin(path).into()),
+            ),
+            Self::Credentials => match field.kind {
+                FieldKind::Leaf { credential: Some(name), .. } => ValueSource::Credential {
+                    name: name.to_owned(),
+                },
+                _ => unreachable!("bug: value loaded from credential for field without one"),
+            },
+            Self::Args(origins) => ValueSource::Arg {
+                name: origins.get(path).cloned()
+                    .unwrap_or_else(|| format!("--{}", missing_origin(path))),
+            },
+            Self::Overrides(origins) => ValueSource::Override {
+                value: origins.get(path).cloned().unwrap_or_else(|| missing_origin(path)),
+            },
+            Self::Preloaded(index) => ValueSource::Preloaded { index: *index },
+            Self::Default => ValueSource::Default,
+        }
+    }
+}
+
+/// Fallback for a value whose entry is missing from the origins map of its
+/// layer. That is a bug in the loader, but not worth a panic: the path is
+/// still a useful description.
+fn missing_origin(path: &str) -> String {
+    debug_assert!(false, "bug: no origin recorded for `{path}`");
+    path.to_owned()
+}
+
+/// Adds warnings about the variables in `vars`: for each deprecated alias key
+/// (`#[config(alias_env = "...")]`) that is used, and for each variable that
+/// starts with the env prefix of `C`, but is not the env key of any field (or
+/// that key with the `_FILE` suffix). The latter check is skipped if `C` has
+/// no env prefix.
+fn warn_env_vars<C: Config>(
+    vars: &dyn EnvSource,
+    warnings: &mut Vec<Warning>,
+    source: impl Fn(String) -> ValueSource,
+) {
+    let is_set = |key: &str| {
+        !matches!(vars.var(key), Err(VarError::NotPresent))
+            || !matches!(vars.var(&format!("{key}_FILE")), Err(VarError::NotPresent))
+    };
+
+    let mut known = HashSet::new();
+    C::META.for_each_leaf(|path, field, scope| {
+        let Some(key) = field.env_key(scope) else { return };
+        let FieldKind::Leaf { env_aliases, .. } = field.kind else { return };
+        for alias in env_aliases {
+            let alias = scope.key(Some(alias), field.name).expect("bug: no key for env alias");
+            if !is_set(&key) && is_set(&alias) {
+                warnings.push(Warning::DeprecatedAlias {
+                    alias: alias.clone(),
+                    replacement: key.clone(),
+                    path: path.to_owned(),
+                    source: source(alias.clone()),
+                });
+            }
+            known.insert(alias);
+        }
+        known.insert(key);
+    });
+
+    let Some(prefix) = C::META.env_prefix.filter(|p| !p.is_empty()) else {
+        return;
+    };
+    let mut unknown = vars.keys().into_iter()
+        .filter(|key| key.starts_with(prefix))
+        .filter(|key| {
+            !known.contains(key)
+                && !key.strip_suffix("_FILE").is_some_and(|key| known.contains(key))
+        })
+        .collect::<Vec<_>>();
+    unknown.sort();
+
+    for key in unknown {
+        let suggestion = dotted::suggestion(known.iter().map(|k| &**k), &key).map(Into::into);
+        warnings.push(Warning::UnknownEnvVar { source: source(key), suggestion });
+    }
+}
+
+/// Adds a warning for each field marked as deprecated that is set by one of
+/// the layers. For deprecated nested fields, only the first value set inside
+/// them is reported per layer.
+fn warn_deprecated<C: Config>(layers: &[Layer<C>], warnings: &mut Vec<Warning>) {
+    let mut deprecated = Vec::new();
+    collect_deprecated(&C::META, "", &mut deprecated);
+    if deprecated.is_empty() {
+        return;
+    }
+
+    for layer in layers {
+        let set_paths = layer.partial.set_paths();
+        for (path, message) in &deprecated {
+            let prefix = format!("{path}.");
+            let Some(set_path) = set_paths.iter()
+                .find(|p| *p == path || p.starts_with(&prefix))
+            else {
+                continue;
+            };
+
+            C::META.for_each_leaf(|leaf_path, field, scope| {
+                if leaf_path == set_path {
+                    warnings.push(Warning::Deprecated {
+                        path: path.clone(),
+                        message: (*message).to_owned(),
+                        source: layer.origin.value_source(leaf_path, field, scope),
+                    });
+                }
+            });
+        }
+    }
+}
+
+/// Adds the paths and messages of all deprecated fields in `meta` to `out`.
+fn collect_deprecated(meta: &Meta, prefix: &str, out: &mut Vec<(String, &'static str)>) {
+    for field in meta.fields {
+        let path = match prefix.is_empty() {
+            true => field.name.to_owned(),
+            false => format!("{prefix}.{}", field.name),
+        };
+        if let Some(message) = field.deprecated {
+            out.push((path.clone(), message));
+        }
+        if let FieldKind::Nested { meta, .. } = field.kind {
+            collect_deprecated(meta, &path, out);
+        }
+    }
+}
+
+/// Returns the env key that the value at `path` was read from, falling back
+/// to the field's env key.
+fn env_key(keys: &HashMap<String, String>, path: &str, field: &Field, scope: &EnvScope) -> String {
+    keys.get(path).cloned().unwrap_or_else(|| {
+        field.env_key(scope).expect("bug: value loaded from env for field without env key")
+    })
+}
+
+/// Returns a map from path to the env key each value is read from by
+/// `Partial::from_env_scoped`: the key itself, `<KEY>_FILE` or one of the
+/// alias keys. Values that are not set in `vars` have no entry.
+fn env_keys_read<C: Config>(vars: &dyn EnvSource, scope: &EnvScope) -> HashMap<String, String> {
+    let mut out = HashMap::new();
+    C::META.for_each_leaf_in(scope, |path, field, scope| {
+        let FieldKind::Leaf { env, env_aliases, env_file_suffix, .. } = field.kind else {
+            return;
+        };
+        let var = scope.var(env, field.name, env_file_suffix);
+        let var = env_var_with_aliases(vars, scope, var, env_aliases);
+        if let Some(key) = var.and_then(|var| var.set_key(vars)) {
+            out.insert(path.to_owned(), key);
+        }
+    });
+    out
+}
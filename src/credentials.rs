@@ -0,0 +1,49 @@
+//! Loading values from systemd credentials (`$CREDENTIALS_DIRECTORY`).
+
+use std::{fs, io, path::Path};
+
+use crate::{
+    dotted,
+    error::{redact_msg, ErrorInner},
+    meta::FieldKind,
+    Config, Error, Partial,
+};
+
+
+/// The env variable set by systemd to the directory containing the
+/// credentials of the service.
+pub(crate) const DIR_ENV_KEY: &str = "CREDENTIALS_DIRECTORY";
+
+/// Loads all leaf fields with `#[config(credential = "...")]` from the
+/// correspondingly named files in `dir`. Missing files are skipped. Trailing
+/// newlines are stripped from the file contents, which are then deserialized
+/// like environment variables.
+pub(crate) fn load<C: Config>(dir: &Path) -> Result<C::Partial, Error> {
+    let mut credentials = Vec::new();
+    C::META.for_each_leaf(|path, field, _| {
+        if let FieldKind::Leaf { credential: Some(name), .. } = field.kind {
+            credentials.push((path.to_owned(), name, field.secret));
+        }
+    });
+
+    let mut partial = C::Partial::empty();
+    for (path, name, secret) in credentials {
+        let file = dir.join(name);
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(ErrorInner::Io { path: Some(file), env_key: None, err }.into()),
+        };
+
+        let value = content.trim_end_matches(['\n', '\r']).to_owned();
+        let layer = dotted::partial_from_path::<C::Partial>(&path, value)
+            .map_err(|e| ErrorInner::KeyFileDeserialization {
+                field: path.clone(),
+                path: file,
+                msg: redact_msg(e.0, secret),
+            })?;
+        partial = partial.with_fallback(layer);
+    }
+
+    Ok(partial)
+}
@@ -0,0 +1,287 @@
+//! Creating partial configurations from a dotted path (e.g. `http.port`) and a
+//! string value. Used by all sources that specify single values by path, like
+//! command line arguments.
+
+use serde::de::{self, IntoDeserializer};
+
+use crate::{
+    env::{self, DeError},
+    meta::{FieldKind, Meta, Type},
+    Partial,
+};
+
+
+/// Deserializes a partial configuration in which only the leaf at `path` is
+/// set. The string `value` is deserialized with the same rules as environment
+/// variables (see [`env::Deserializer`]).
+///
+/// The caller has to make sure that `path` refers to a leaf field, as unknown
+/// fields are silently ignored by the partial types.
+pub(crate) fn partial_from_path<P: Partial>(path: &str, value: String) -> Result<P, DeError> {
+    let segments = path.split('.').collect::<Vec<_>>();
+    P::deserialize(PathDeserializer { segments: &segments, value })
+}
+
+/// A leaf field of a configuration.
+pub(crate) struct Leaf {
+    pub(crate) path: String,
+    pub(crate) secret: bool,
+    pub(crate) ty: Type,
+}
+
+/// Returns all leaf fields of `meta`, in definition order.
+pub(crate) fn leaves(meta: &Meta) -> Vec<Leaf> {
+    let mut out = Vec::new();
+    meta.for_each_leaf(|path, field, _| {
+        let ty = match field.kind {
+            FieldKind::Leaf { ty, .. } => ty,
+            FieldKind::Nested { .. } => unreachable!("`for_each_leaf` only visits leaves"),
+        };
+        out.push(Leaf { path: path.to_owned(), secret: field.secret, ty });
+    });
+    out
+}
+
+/// Returns the leaf `name` refers to. Dashes in `name` can be used instead of
+/// underscores.
+pub(crate) fn find_leaf<'a>(leaves: &'a [Leaf], name: &str) -> Option<&'a Leaf> {
+    let underscored = name.replace('-', "_");
+    leaves.iter().find(|leaf| leaf.path == name || leaf.path == underscored)
+}
+
+/// Returns the paths of all `leaves`.
+pub(crate) fn paths(leaves: &[Leaf]) -> impl Iterator<Item = &str> {
+    leaves.iter().map(|leaf| &*leaf.path)
+}
+
+/// Returns the candidate most similar to `name`, if any is similar enough to
+/// likely be meant by the user (i.e. a typo).
+pub(crate) fn suggestion<'a>(
+    candidates: impl IntoIterator<Item = &'a str>,
+    name: &str,
+) -> Option<&'a str> {
+    let max_distance = std::cmp::max(1, name.chars().count() / 3);
+    candidates.into_iter()
+        .map(|c| (edit_distance(c, name), c))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr.push(substitution.min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Deserializer for a map with a single entry `segments[0]`, recursively
+/// nested until there are no segments left, at which point `value` is
+/// deserialized.
+struct PathDeserializer<'a> {
+    segments: &'a [&'a str],
+    value: String,
+}
+
+macro_rules! forward_leaf {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                match self.segments.is_empty() {
+                    true => de::Deserializer::$method(env::Deserializer::new(self.value), visitor),
+                    false => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for PathDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.segments.split_first() {
+            Some((key, rest)) => visitor.visit_map(SingleEntry {
+                key: Some(key),
+                value: Some(PathDeserializer { segments: rest, value: self.value }),
+            }),
+            None => env::Deserializer::new(self.value).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // All leaf fields in the partial types are `Option`s, so we land here
+        // for every leaf.
+        if self.segments.is_empty() {
+            visitor.visit_some(env::Deserializer::new(self.value))
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    // All other methods are forwarded to `env::Deserializer` for leaf values,
+    // and to `deserialize_any` (i.e. map) otherwise.
+    forward_leaf! {
+        deserialize_bool,
+        deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64,
+        deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64,
+        deserialize_f32, deserialize_f64,
+        deserialize_char, deserialize_str, deserialize_string,
+        deserialize_bytes, deserialize_byte_buf,
+        deserialize_unit, deserialize_seq, deserialize_map,
+        deserialize_identifier, deserialize_ignored_any,
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.segments.is_empty() {
+            true => env::Deserializer::new(self.value).deserialize_unit_struct(name, visitor),
+            false => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.segments.is_empty() {
+            true => env::Deserializer::new(self.value).deserialize_newtype_struct(name, visitor),
+            false => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.segments.is_empty() {
+            true => env::Deserializer::new(self.value).deserialize_tuple(len, visitor),
+            false => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.segments.is_empty() {
+            true => env::Deserializer::new(self.value)
+                .deserialize_tuple_struct(name, len, visitor),
+            false => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.segments.is_empty() {
+            true => env::Deserializer::new(self.value).deserialize_struct(name, fields, visitor),
+            false => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.segments.is_empty() {
+            true => env::Deserializer::new(self.value).deserialize_enum(name, variants, visitor),
+            false => self.deserialize_any(visitor),
+        }
+    }
+}
+
+/// Map access for a map with exactly one entry.
+struct SingleEntry<'a> {
+    key: Option<&'a str>,
+    value: Option<PathDeserializer<'a>>,
+}
+
+impl<'de> de::MapAccess<'de> for SingleEntry<'_> {
+    type Error = DeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        self.key.take()
+            .map(|key| seed.deserialize(key.into_deserializer()))
+            .transpose()
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self.value.take().expect("bug: `next_value_seed` called twice");
+        seed.deserialize(value)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, suggestion};
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("port", "port"), 0);
+        assert_eq!(edit_distance("port", "prot"), 2);
+        assert_eq!(edit_distance("log.level", "log.levle"), 2);
+        assert_eq!(edit_distance("log.file", "log.files"), 1);
+        assert_eq!(edit_distance("abc", ""), 3);
+    }
+
+    #[test]
+    fn suggest() {
+        let paths = ["name", "log.level", "log.file"];
+        assert_eq!(suggestion(paths, "log.levl"), Some("log.level"));
+        assert_eq!(suggestion(paths, "log.fil"), Some("log.file"));
+        assert_eq!(suggestion(paths, "nmae"), None);
+        assert_eq!(suggestion(paths, "http.port"), None);
+    }
+}
@@ -0,0 +1,210 @@
+//! Parsing of dotenv-style files (`.env`).
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use crate::{error::{ErrorInner, Location}, Error};
+
+
+/// Loads and parses the dotenv file at `path`. If the file does not exist, an
+/// empty map is returned.
+pub(crate) fn load(path: &Path) -> Result<HashMap<String, String>, Error> {
+    let content = match fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => {
+            return Err(ErrorInner::Io { path: Some(path.to_owned()), env_key: None, err }.into());
+        }
+    };
+
+    parse(&content)
+        .map(|vars| vars.into_iter().collect())
+        .map_err(|err| ErrorInner::Deserialization {
+            source: Some(format!("env file '{}'", path.display())),
+            file: Some(path.to_owned()),
+            path: None,
+            location: Some(Location::from_line_column(&content, err.line, 1)),
+            err: Box::new(err),
+        }.into())
+}
+
+/// Parses the contents of a dotenv file. Returns all variables in order of
+/// appearance (later definitions of the same key are not removed).
+///
+/// Supported syntax:
+/// - `KEY=VALUE` lines, optionally prefixed with `export `. Whitespace around
+///   the key and value is ignored.
+/// - Empty lines and lines starting with `#` are ignored.
+/// - Unquoted values end at ` #` (inline comment).
+/// - Single quoted values are taken literally and can span multiple lines.
+/// - Double quoted values can span multiple lines and support the escape
+///   sequences `\n`, `\r`, `\t`, `\"`, `\\` and `\$`.
+///
+/// Variable expansion (`${FOO}`) is not supported.
+pub(crate) fn parse(input: &str) -> Result<Vec<(String, String)>, ParseError> {
+    let mut out = Vec::new();
+    let mut lines = input.lines().enumerate().map(|(i, line)| (i + 1, line));
+    while let Some((line_no, line)) = lines.next() {
+        let err = |msg| ParseError { line: line_no, msg };
+
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+            .map(str::trim_start)
+            .unwrap_or(line);
+
+        let (key, value) = line.split_once('=').ok_or(err("expected `KEY=VALUE`"))?;
+        let key = key.trim_end();
+        let valid_key = !key.is_empty()
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        if !valid_key {
+            return Err(err("invalid key"));
+        }
+
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut raw = value[1..].to_owned();
+                let end = loop {
+                    if let Some(end) = find_closing_quote(&raw, quote) {
+                        break end;
+                    }
+                    match lines.next() {
+                        Some((_, next)) => {
+                            raw.push('\n');
+                            raw.push_str(next);
+                        }
+                        None => return Err(err("unterminated quoted value")),
+                    }
+                };
+
+                let rest = raw[end + 1..].trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    return Err(err("unexpected characters after quoted value"));
+                }
+
+                let inner = &raw[..end];
+                if quote == '"' { unescape(inner) } else { inner.to_owned() }
+            }
+            _ => {
+                let end = value.find(" #").unwrap_or(value.len());
+                value[..end].trim_end().to_owned()
+            }
+        };
+
+        out.push((key.to_owned(), value));
+    }
+
+    Ok(out)
+}
+
+/// Returns the byte index of the closing quote in `s`, skipping escaped quotes
+/// in double quoted strings.
+fn find_closing_quote(s: &str, quote: char) -> Option<usize> {
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => { chars.next(); }
+            c if c == quote => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(c @ ('"' | '\\' | '$')) => out.push(c),
+            Some(c) => {
+                out.push('\\');
+                out.push(c);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Syntax error in a dotenv file.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ParseError {
+    line: usize,
+    msg: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, ParseError};
+
+    fn check(input: &str, expected: &[(&str, &str)]) {
+        let expected = expected.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(parse(input), Ok(expected));
+    }
+
+    fn check_err(input: &str, line: usize, msg: &'static str) {
+        assert_eq!(parse(input), Err(ParseError { line, msg }));
+    }
+
+    #[test]
+    fn simple() {
+        check("FOO=bar\nBAZ = 3 \n\n", &[("FOO", "bar"), ("BAZ", "3")]);
+        check("export FOO=bar", &[("FOO", "bar")]);
+        check("exported=1", &[("exported", "1")]);
+        check("EMPTY=\nA=x", &[("EMPTY", ""), ("A", "x")]);
+        check("URL=http://a.b/?x=1", &[("URL", "http://a.b/?x=1")]);
+        check("A=1\nA=2", &[("A", "1"), ("A", "2")]);
+    }
+
+    #[test]
+    fn comments() {
+        check("# comment\n  # indented\nFOO=bar # inline\nBAR=a#b", &[
+            ("FOO", "bar"),
+            ("BAR", "a#b"),
+        ]);
+    }
+
+    #[test]
+    fn quotes() {
+        check(r#"A="x # y""#, &[("A", "x # y")]);
+        check(r#"A='x\ny' # comment"#, &[("A", r"x\ny")]);
+        check(r#"A="x\ny\t\"z\" \$HOME \\""#, &[("A", "x\ny\t\"z\" $HOME \\")]);
+        check("A=\"multi\nline\"\nB='also\n multi'", &[
+            ("A", "multi\nline"),
+            ("B", "also\n multi"),
+        ]);
+    }
+
+    #[test]
+    fn errors() {
+        check_err("A=1\nFOO", 2, "expected `KEY=VALUE`");
+        check_err("=1", 1, "invalid key");
+        check_err("A B=1", 1, "invalid key");
+        check_err("A=\"foo\nbar", 1, "unterminated quoted value");
+        check_err("A='foo' bar", 1, "unexpected characters after quoted value");
+    }
+}
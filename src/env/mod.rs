@@ -1,13 +1,86 @@
 //! Deserialize values from environment variables.
 
-use std::fmt;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env::VarError,
+    fmt,
+    hash::BuildHasher,
+};
 
 use serde::de::IntoDeserializer;
 
 
+pub(crate) mod dotenv;
 pub mod parse;
 
 
+/// A set of environment variables that configuration values can be loaded
+/// from.
+///
+/// Implemented by [`ProcessEnv`], which reads the environment of the current
+/// process, and maps from keys to values. The latter are useful to load
+/// configuration in tests without modifying the global process environment.
+/// See [`Partial::from_env_source`][crate::Partial::from_env_source] and
+/// [`Builder::env_from`][crate::Builder::env_from].
+pub trait EnvSource {
+    /// Returns the value of the variable `key`. Has the same semantics as
+    /// [`std::env::var`].
+    fn var(&self, key: &str) -> Result<String, VarError>;
+
+    /// Returns the keys of all variables in this source. Only used to warn
+    /// about unknown variables (see
+    /// [`Builder::load_with_warnings`][crate::Builder::load_with_warnings]).
+    /// The default implementation returns an empty list.
+    fn keys(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The environment of the current process, accessed via [`std::env::var`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn var(&self, key: &str) -> Result<String, VarError> {
+        std::env::var(key)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        std::env::vars_os().filter_map(|(key, _)| key.into_string().ok()).collect()
+    }
+}
+
+impl<S: BuildHasher> EnvSource for HashMap<String, String, S> {
+    fn var(&self, key: &str) -> Result<String, VarError> {
+        self.get(key).cloned().ok_or(VarError::NotPresent)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        HashMap::keys(self).cloned().collect()
+    }
+}
+
+impl EnvSource for BTreeMap<String, String> {
+    fn var(&self, key: &str) -> Result<String, VarError> {
+        self.get(key).cloned().ok_or(VarError::NotPresent)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        BTreeMap::keys(self).cloned().collect()
+    }
+}
+
+impl<T: EnvSource + ?Sized> EnvSource for &T {
+    fn var(&self, key: &str) -> Result<String, VarError> {
+        (**self).var(key)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        (**self).keys()
+    }
+}
+
+
 /// Error type only for deserialization of env values.
 ///
 /// Semantically private, only public as it's used in the API of the `internal`
@@ -37,6 +37,7 @@ fn ints() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn floats() {
     assert_eq!(de("3.1415"), Ok(3.1415f32));
     assert_eq!(de("-123.456"), Ok(-123.456f64));
@@ -1,12 +1,100 @@
 use std::fmt;
 
-use std::path::PathBuf;
+use std::{ops::Range, path::{Path, PathBuf}};
+
+use crate::ValueSource;
 
 
 
 /// Type describing all errors that can occur in this library.
+///
+/// Use [`Error::kind`] and the other accessors to inspect the error, e.g. to
+/// decide how to handle it or to render it in your own way.
 pub struct Error {
     pub(crate) inner: Box<ErrorInner>,
+
+    /// The layer this error occured in, set by `Builder`.
+    layer: Option<SourceLayer>,
+}
+
+/// The kind of an [`Error`], returned by [`Error::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A required value is not set by any source.
+    MissingValue,
+
+    /// A value or struct failed validation (`#[config(validate = ...)]` or a
+    /// constraint like `min`).
+    Validation,
+
+    /// An IO error occured, e.g. when reading a file.
+    Io,
+
+    /// A configuration file or env file could not be deserialized, e.g. due to
+    /// a syntax error or a value of the wrong type.
+    Deserialization,
+
+    /// The format of a configuration file is unknown or could not be inferred
+    /// from its extension.
+    UnsupportedFileFormat,
+
+    /// A file marked as required does not exist.
+    MissingRequiredFile,
+
+    /// The value of an environment variable is invalid.
+    InvalidEnvValue,
+
+    /// A command line argument is malformed or refers to an unknown value.
+    InvalidArg,
+
+    /// The value of a command line argument is invalid.
+    InvalidArgValue,
+
+    /// An override is malformed or refers to an unknown value.
+    InvalidOverride,
+
+    /// The value of an override is invalid.
+    InvalidOverrideValue,
+
+    /// The value in a file of a key directory or of a systemd credential is
+    /// invalid.
+    InvalidKeyFileValue,
+
+    /// A configuration file or key directory contains a key that does not
+    /// correspond to any configuration value (only in strict mode).
+    UnknownKey,
+
+    /// A configuration could not be serialized, e.g. by `toml::to_string`.
+    Serialization,
+
+    /// `--help` was passed as command line argument, see
+    /// [`Error::is_help_request`].
+    HelpRequested,
+
+    /// Multiple errors occured, see [`Error::errors`].
+    Multiple,
+}
+
+/// The kind of source in which an error occured, returned by
+/// [`Error::source_layer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SourceLayer {
+    /// A configuration file (`Builder::file` or `Builder::dir`).
+    File,
+    /// Environment variables (`Builder::env` or `Builder::env_from`).
+    Env,
+    /// An env file (`Builder::env_file`).
+    EnvFile,
+    /// A key directory (`Builder::key_dir`).
+    KeyDir,
+    /// Systemd credentials (`Builder::systemd_credentials`).
+    Credentials,
+    /// Command line arguments (`Builder::args`).
+    Args,
+    /// Overrides (`Builder::overrides`).
+    Overrides,
 }
 
 // If all these features are disabled, lots of these errors are unused. But
@@ -23,9 +111,21 @@ pub(crate) enum ErrorInner {
     /// human-readable path to the value, e.g. `http.port`.
     MissingValue(String),
 
+    /// Returned by `Config::from_partial` when a validation function
+    /// (`#[config(validate = ...)]`) fails. `path` is the dotted path to the
+    /// validated field or struct, and empty for the root struct.
+    Validation {
+        path: String,
+        err: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     /// An IO error occured, e.g. when reading a file.
     Io {
         path: Option<PathBuf>,
+
+        /// Set if the file path was specified by this env variable (a
+        /// `_FILE` suffixed key).
+        env_key: Option<String>,
         err: std::io::Error,
     },
 
@@ -36,6 +136,15 @@ pub(crate) enum ErrorInner {
         /// "failed to deserialize configuration from ". E.g. "file 'foo.toml'"
         /// or "environment variable 'FOO_PORT'".
         source: Option<String>,
+
+        /// The path of the file that failed to deserialize.
+        file: Option<PathBuf>,
+
+        /// The dotted path of the value that failed to deserialize, if known.
+        path: Option<String>,
+
+        /// Where in the file the error occured, if known.
+        location: Option<Location>,
         err: Box<dyn std::error::Error + Send + Sync>,
     },
 
@@ -68,20 +177,276 @@ pub(crate) enum ErrorInner {
 
     /// A file source was marked as required but the file does not exist.
     MissingRequiredFile { path: PathBuf },
+
+    /// A command line argument is malformed or refers to an unknown
+    /// configuration value.
+    InvalidArg { arg: String, msg: String },
+
+    /// When deserialization of a command line argument value fails. The
+    /// string is what is passed to `serde::de::Error::custom`.
+    ArgDeserialization {
+        field: String,
+        arg: String,
+        msg: String,
+    },
+
+    /// An override passed to `Builder::overrides` is malformed or refers to an
+    /// unknown configuration value.
+    InvalidOverride { value: String, msg: String },
+
+    /// When deserialization of an override value fails. The string is what is
+    /// passed to `serde::de::Error::custom`.
+    OverrideDeserialization {
+        field: String,
+        value: String,
+        msg: String,
+    },
+
+    /// When deserialization of a value loaded from a file in a key directory
+    /// (`Builder::key_dir`) or from a systemd credential fails. The string is what is passed to
+    /// `serde::de::Error::custom`.
+    KeyFileDeserialization {
+        field: String,
+        path: PathBuf,
+        msg: String,
+    },
+
+    /// A file contains a key that does not correspond to any configuration
+    /// value, and strict mode is enabled. `path` is the dotted path of the
+    /// key, `suggestion` the dotted path of a similar existing value and
+    /// `source` the file (or key file) containing the key.
+    UnknownKey {
+        path: String,
+        suggestion: Option<String>,
+        source: ValueSource,
+    },
+
+    /// Serializing a configuration (e.g. via `toml::to_string`) failed. `path`
+    /// is the dotted path of the value that could not be serialized, if
+    /// known, and `msg` what is passed to `serde::ser::Error::custom`.
+    Serialization { path: Option<String>, msg: String },
+
+    /// `--help` or `-h` was passed as command line argument. Contains the
+    /// rendered help text.
+    HelpRequested { help: String },
+
+    /// Multiple errors occured while loading. Contains at least two errors,
+    /// none of which is `Multiple` itself.
+    Multiple(Vec<Error>),
+}
+
+impl Error {
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        match &*self.inner {
+            ErrorInner::MissingValue(_) => ErrorKind::MissingValue,
+            ErrorInner::Validation { .. } => ErrorKind::Validation,
+            ErrorInner::Io { .. } => ErrorKind::Io,
+            ErrorInner::Deserialization { .. } => ErrorKind::Deserialization,
+            ErrorInner::EnvNotUnicode { .. }
+            | ErrorInner::EnvDeserialization { .. }
+            | ErrorInner::EnvParseError { .. } => ErrorKind::InvalidEnvValue,
+            ErrorInner::UnsupportedFileFormat { .. }
+            | ErrorInner::MissingFileExtension { .. } => ErrorKind::UnsupportedFileFormat,
+            ErrorInner::MissingRequiredFile { .. } => ErrorKind::MissingRequiredFile,
+            ErrorInner::InvalidArg { .. } => ErrorKind::InvalidArg,
+            ErrorInner::ArgDeserialization { .. } => ErrorKind::InvalidArgValue,
+            ErrorInner::InvalidOverride { .. } => ErrorKind::InvalidOverride,
+            ErrorInner::OverrideDeserialization { .. } => ErrorKind::InvalidOverrideValue,
+            ErrorInner::KeyFileDeserialization { .. } => ErrorKind::InvalidKeyFileValue,
+            ErrorInner::UnknownKey { .. } => ErrorKind::UnknownKey,
+            ErrorInner::Serialization { .. } => ErrorKind::Serialization,
+            ErrorInner::HelpRequested { .. } => ErrorKind::HelpRequested,
+            ErrorInner::Multiple(_) => ErrorKind::Multiple,
+        }
+    }
+
+    /// Returns the environment variable this error is about, if any. For
+    /// values read from a file specified via a `_FILE` suffixed variable, that
+    /// variable is returned.
+    pub fn env_key(&self) -> Option<&str> {
+        match &*self.inner {
+            ErrorInner::Io { env_key, .. } => env_key.as_deref(),
+            ErrorInner::EnvNotUnicode { key, .. }
+            | ErrorInner::EnvDeserialization { key, .. }
+            | ErrorInner::EnvParseError { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the file this error is about, if any (e.g. the
+    /// configuration file that failed to deserialize).
+    pub fn file_path(&self) -> Option<&Path> {
+        match &*self.inner {
+            ErrorInner::Io { path, .. } => path.as_deref(),
+            ErrorInner::Deserialization { file, .. } => file.as_deref(),
+            ErrorInner::UnknownKey {
+                source: ValueSource::File(path) | ValueSource::KeyFile(path),
+                ..
+            } => Some(path),
+            ErrorInner::UnsupportedFileFormat { path }
+            | ErrorInner::MissingFileExtension { path }
+            | ErrorInner::MissingRequiredFile { path }
+            | ErrorInner::KeyFileDeserialization { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind of source in which this error occured, if it occured
+    /// while loading a source via [`Builder`][crate::Builder]. Errors about
+    /// the merged configuration (e.g. missing values) return `None`.
+    pub fn source_layer(&self) -> Option<SourceLayer> {
+        self.layer
+    }
+
+    /// Sets the layer of this error (and all contained errors) unless already
+    /// set.
+    pub(crate) fn with_layer(mut self, layer: SourceLayer) -> Self {
+        self.layer.get_or_insert(layer);
+        if let ErrorInner::Multiple(errors) = &mut *self.inner {
+            for err in std::mem::take(errors) {
+                errors.push(err.with_layer(layer));
+            }
+        }
+        self
+    }
+
+    /// Returns `true` if this error was caused by `--help` or `-h` being
+    /// passed to [`Builder::args`][crate::Builder::args]. In that case, the
+    /// `Display` output of this error is the help text, which should usually
+    /// be printed to stdout before exiting successfully.
+    pub fn is_help_request(&self) -> bool {
+        matches!(*self.inner, ErrorInner::HelpRequested { .. })
+    }
+
+    /// Returns an iterator over all individual errors. Loading does not stop
+    /// at the first problem, but reports all missing values, invalid env
+    /// variables, failed validations and so on at once. If only one error
+    /// occured, the iterator yields only `self`.
+    ///
+    /// The `Display` output of this error lists all individual errors.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Debug, Config)]
+    /// struct Conf {
+    ///     port: u16,
+    ///     host: String,
+    /// }
+    ///
+    /// let err = Conf::builder().load().unwrap_err();
+    /// let messages = err.errors().map(|e| e.to_string()).collect::<Vec<_>>();
+    /// assert_eq!(messages, [
+    ///     "required configuration value is missing: 'port'",
+    ///     "required configuration value is missing: 'host'",
+    /// ]);
+    /// ```
+    pub fn errors(&self) -> impl Iterator<Item = &Error> {
+        match &*self.inner {
+            ErrorInner::Multiple(errors) => errors.iter(),
+            _ => std::slice::from_ref(self).iter(),
+        }
+    }
+
+    /// Returns the dotted path (e.g. `http.tls.port`) of the configuration
+    /// value this error is about, if it is about a single value and the path
+    /// is known. For errors containing multiple errors, `None` is returned
+    /// (see [`Error::errors`]).
+    pub fn field_path(&self) -> Option<&str> {
+        match &*self.inner {
+            ErrorInner::MissingValue(path) => Some(path),
+            ErrorInner::Validation { path, .. } if !path.is_empty() => Some(path),
+            ErrorInner::Deserialization { path, .. } => path.as_deref(),
+            ErrorInner::EnvNotUnicode { field, .. }
+            | ErrorInner::EnvDeserialization { field, .. }
+            | ErrorInner::EnvParseError { field, .. }
+            | ErrorInner::ArgDeserialization { field, .. }
+            | ErrorInner::OverrideDeserialization { field, .. }
+            | ErrorInner::KeyFileDeserialization { field, .. } => Some(field),
+            ErrorInner::Serialization { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the position in the configuration file at which loading
+    /// failed, if this error was caused by an invalid file and the position is
+    /// known. The alternate `Display` output (`{:#}`) of such errors includes
+    /// the offending line with the position marked.
+    pub fn location(&self) -> Option<&Location> {
+        match &*self.inner {
+            ErrorInner::Deserialization { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Combines the given errors into one, flattening nested `Multiple`
+    /// errors. Returns `None` if `errors` is empty.
+    pub(crate) fn combine(errors: Vec<Error>) -> Option<Error> {
+        let mut flat = errors.into_iter().flat_map(Error::into_errors).collect::<Vec<_>>();
+
+        match flat.len() {
+            0 => None,
+            1 => flat.pop(),
+            _ => Some(ErrorInner::Multiple(flat).into()),
+        }
+    }
+
+    /// Returns the individual errors, like [`Error::errors`], but owned.
+    pub(crate) fn into_errors(self) -> Vec<Error> {
+        match *self.inner {
+            ErrorInner::Multiple(errors) => errors,
+            _ => vec![self],
+        }
+    }
+
+    /// If this is a deserialization error of a value belonging to a secret
+    /// field in `meta`, replaces the message (which might contain the value)
+    /// and removes the snippet from the location, as it shows the value.
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    pub(crate) fn redact_secret(mut self, meta: &crate::meta::Meta) -> Self {
+        if let ErrorInner::Deserialization { path: Some(path), location, err, .. }
+            = &mut *self.inner
+        {
+            if meta.is_secret(path) {
+                *err = Box::new(RedactedError);
+                if let Some(location) = location {
+                    location.line_text = None;
+                }
+            }
+        }
+        self
+    }
+
+    pub(crate) fn is_missing_value(&self) -> bool {
+        matches!(*self.inner, ErrorInner::MissingValue(_))
+    }
 }
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &*self.inner {
             ErrorInner::Io { err, .. } => Some(err),
+            // With a path, the message of `err` is included in `Display`.
+            ErrorInner::Deserialization { path: Some(_), err, .. } => err.source(),
             ErrorInner::Deserialization { err, .. } => Some(&**err),
             ErrorInner::MissingValue(_) => None,
+            ErrorInner::Validation { err, .. } => Some(&**err),
             ErrorInner::EnvNotUnicode { .. } => None,
             ErrorInner::EnvDeserialization { .. } => None,
             ErrorInner::EnvParseError { err, .. } => Some(&**err),
             ErrorInner::UnsupportedFileFormat { .. } => None,
             ErrorInner::MissingFileExtension { .. } => None,
             ErrorInner::MissingRequiredFile { .. } => None,
+            ErrorInner::InvalidArg { .. } => None,
+            ErrorInner::ArgDeserialization { .. } => None,
+            ErrorInner::InvalidOverride { .. } => None,
+            ErrorInner::OverrideDeserialization { .. } => None,
+            ErrorInner::KeyFileDeserialization { .. } => None,
+            ErrorInner::UnknownKey { .. } => None,
+            ErrorInner::Serialization { .. } => None,
+            ErrorInner::HelpRequested { .. } => None,
+            ErrorInner::Multiple(_) => None,
         }
     }
 }
@@ -92,6 +457,19 @@ impl fmt::Display for Error {
             ErrorInner::MissingValue(path) => {
                 std::write!(f, "required configuration value is missing: '{path}'")
             }
+            ErrorInner::Validation { path, err } if path.is_empty() => {
+                std::write!(f, "invalid configuration: {err}")
+            }
+            ErrorInner::Validation { path, err } => {
+                std::write!(f, "invalid configuration value `{path}`: {err}")
+            }
+            ErrorInner::Io { path: Some(path), env_key: Some(key), .. } => {
+                std::write!(f,
+                    "IO error occured while reading file '{}' specified by environment \
+                        variable `{key}`",
+                    path.display(),
+                )
+            }
             ErrorInner::Io { path: Some(path), .. } => {
                 std::write!(f,
                     "IO error occured while reading configuration file '{}'",
@@ -101,11 +479,28 @@ impl fmt::Display for Error {
             ErrorInner::Io { path: None, .. } => {
                 std::write!(f, "IO error occured while loading configuration")
             }
-            ErrorInner::Deserialization { source: Some(source), .. } => {
-                std::write!(f, "failed to deserialize configuration from {source}")
-            }
-            ErrorInner::Deserialization { source: None, .. } => {
-                std::write!(f, "failed to deserialize configuration")
+            ErrorInner::Deserialization { source, path, location, err, .. } => {
+                match path {
+                    Some(path) => std::write!(f, "failed to deserialize value `{path}`")?,
+                    None => f.write_str("failed to deserialize configuration")?,
+                }
+                if let Some(source) = source {
+                    std::write!(f, " from {source}")?;
+                }
+                if let Some(location) = location {
+                    std::write!(f, " at line {}, column {}", location.line, location.column)?;
+                }
+
+                // Errors for a specific value are short, like "invalid type:
+                // string "x", expected u16". Errors without path are usually
+                // syntax errors that are better shown separately via `source`.
+                if path.is_some() {
+                    std::write!(f, ": {err}")?;
+                }
+                if let (Some(location), true) = (location, f.alternate()) {
+                    location.render_snippet(f)?;
+                }
+                Ok(())
             }
             ErrorInner::EnvNotUnicode { field, key } => {
                 std::write!(f, "failed to load value `{field}` from \
@@ -137,8 +532,162 @@ impl fmt::Display for Error {
                     path.display(),
                 )
             }
+            ErrorInner::InvalidArg { arg, msg } => {
+                std::write!(f, "invalid command line argument `{arg}`: {msg}")
+            }
+            ErrorInner::ArgDeserialization { field, arg, msg } => {
+                std::write!(f, "failed to deserialize value `{field}` from \
+                    command line argument `{arg}`: {msg}")
+            }
+            ErrorInner::InvalidOverride { value, msg } => {
+                std::write!(f, "invalid configuration override `{value}`: {msg}")
+            }
+            ErrorInner::OverrideDeserialization { field, value, msg } => {
+                std::write!(f, "failed to deserialize value `{field}` from \
+                    override `{value}`: {msg}")
+            }
+            ErrorInner::KeyFileDeserialization { field, path, msg } => {
+                std::write!(f, "failed to deserialize value `{field}` from \
+                    file '{}': {msg}", path.display())
+            }
+            ErrorInner::UnknownKey { path, suggestion, source } => {
+                std::write!(f, "unknown configuration key `{path}` in {source}")?;
+                if let Some(suggestion) = suggestion {
+                    std::write!(f, " (did you mean `{suggestion}`?)")?;
+                }
+                Ok(())
+            }
+            ErrorInner::Serialization { path: Some(path), msg } => {
+                std::write!(f, "failed to serialize value `{path}`: {msg}")
+            }
+            ErrorInner::Serialization { path: None, msg } => {
+                std::write!(f, "failed to serialize configuration: {msg}")
+            }
+            ErrorInner::HelpRequested { help } => f.write_str(help),
+            ErrorInner::Multiple(errors) => {
+                // As `source` is not available for the individual errors, we
+                // include their source chain in the message, except for the
+                // parts already included in their `Display` output.
+                std::write!(f, "{} errors occured while loading the configuration:", errors.len())?;
+                for err in errors {
+                    std::write!(f, "\n- {err}")?;
+                    let mut source = match &*err.inner {
+                        ErrorInner::Validation { err, .. }
+                        | ErrorInner::EnvParseError { err, .. } => err.source(),
+                        _ => std::error::Error::source(err),
+                    };
+                    while let Some(s) = source {
+                        std::write!(f, ": {s}")?;
+                        source = s.source();
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Position of an error in a configuration file, see [`Error::location`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    line: usize,
+    column: usize,
+    span: Range<usize>,
+
+    /// The line containing the start of `span`, without line break. `None` if
+    /// the value is secret, in which case no snippet is rendered.
+    line_text: Option<String>,
+}
+
+impl Location {
+    /// Creates a location from the byte range `span` in `content`.
+    pub(crate) fn from_span(content: &str, span: Range<usize>) -> Self {
+        let start = floor_char_boundary(content, span.start);
+        let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = content[start..].find('\n').map_or(content.len(), |i| start + i);
+        Self {
+            line: content[..start].matches('\n').count() + 1,
+            column: content[line_start..start].chars().count() + 1,
+            span,
+            line_text: Some(content[line_start..line_end].trim_end_matches('\r').to_owned()),
         }
     }
+
+    /// Creates a location from a one-based `line` and `column` (in characters)
+    /// in `content`. The span is empty.
+    pub(crate) fn from_line_column(content: &str, line: usize, column: usize) -> Self {
+        let line_start = content.split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>();
+        let offset = content[line_start..].char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(content.len(), |(i, _)| line_start + i);
+        Self::from_span(content, offset..offset)
+    }
+
+    /// The one-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The one-based column number, counted in characters.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The byte range in the file. Empty if the backend only reported a
+    /// position.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Renders the offending line with carets below the span, like compiler
+    /// diagnostics.
+    fn render_snippet(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(line_text) = &self.line_text else {
+            return Ok(());
+        };
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        // The span can start at the end of the line (e.g. at a `\r`).
+        let len_in_line = (line_text.chars().count() + 1).saturating_sub(self.column);
+        let carets = self.span.len().clamp(1, len_in_line.max(1));
+        std::write!(f,
+            "\n{gutter} |\n{} | {}\n{gutter} | {}{}",
+            self.line,
+            line_text,
+            " ".repeat(self.column.saturating_sub(1)),
+            "^".repeat(carets),
+        )
+    }
+}
+
+/// Returns the largest char boundary in `s` that is not larger than `index`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Replaces error messages that might contain secret values (of fields with
+/// `#[config(secret)]`).
+#[derive(Debug)]
+pub(crate) struct RedactedError;
+
+impl fmt::Display for RedactedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid value (details are redacted as the value is secret)")
+    }
+}
+
+impl std::error::Error for RedactedError {}
+
+/// Returns `msg`, or a redacted message if the value is `secret`.
+pub(crate) fn redact_msg(msg: String, secret: bool) -> String {
+    if secret { RedactedError.to_string() } else { msg }
 }
 
 impl fmt::Debug for Error {
@@ -149,6 +698,6 @@ impl fmt::Debug for Error {
 
 impl From<ErrorInner> for Error {
     fn from(inner: ErrorInner) -> Self {
-        Self { inner: Box::new(inner) }
+        Self { inner: Box::new(inner), layer: None }
     }
 }
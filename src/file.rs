@@ -1,6 +1,13 @@
-use std::{ffi::OsStr, fs, io, path::PathBuf};
+use std::{ffi::OsStr, fs, io, path::{Path, PathBuf}};
 
-use crate::{error::ErrorInner, Error, Partial};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    error::{ErrorInner, Location},
+    internal::track_de_path,
+    strict::{self, Keys},
+    Error, Partial, Warning,
+};
 
 
 /// A file as source for configuration.
@@ -12,6 +19,7 @@ pub struct File {
     path: PathBuf,
     format: FileFormat,
     required: bool,
+    strict: bool,
 }
 
 impl File {
@@ -35,6 +43,7 @@ impl File {
             path: path.into(),
             format,
             required: false,
+            strict: false,
         }
     }
 
@@ -46,8 +55,37 @@ impl File {
         self
     }
 
+    /// Enables strict mode: [`File::load`] returns an error for every key in
+    /// the file that does not correspond to a configuration value, at any
+    /// nesting level. Each error suggests a similarly named value, if there
+    /// is one. By default, unknown keys are silently ignored.
+    ///
+    /// Note that keys inside leaf values (e.g. of a `HashMap` field) are not
+    /// checked. Keys are checked against the field names in
+    /// [`Config::META`][crate::Config::META], so serde attributes changing the
+    /// keys of the partial type, like
+    /// `#[config(partial_attr(serde(rename_all = "...")))]`, lead to false
+    /// errors. Use `#[config(rename_all = "...")]` instead.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
     /// Attempts to load the file into the partial configuration `P`.
     pub fn load<P: Partial>(&self) -> Result<P, Error> {
+        self.load_impl(None)
+    }
+
+    /// Like [`File::load`], but additionally adds warnings about a missing
+    /// file and unknown keys (if not in strict mode) to `warnings`.
+    pub(crate) fn load_with_warnings<P: Partial>(
+        &self,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<P, Error> {
+        self.load_impl(Some(warnings))
+    }
+
+    fn load_impl<P: Partial>(&self, warnings: Option<&mut Vec<Warning>>) -> Result<P, Error> {
         // Load file contents. If the file does not exist and was not marked as
         // required, we just return an empty layer.
         let file_content = match fs::read(&self.path) {
@@ -56,45 +94,148 @@ impl File {
                 if self.required {
                     return Err(ErrorInner::MissingRequiredFile { path: self.path.clone() }.into());
                 } else {
+                    if let Some(warnings) = warnings {
+                        warnings.push(Warning::MissingFile { path: self.path.clone() });
+                    }
                     return Ok(P::empty());
                 }
             }
             Err(e) => {
                 return Err(ErrorInner::Io {
                     path: Some(self.path.clone()),
+                    env_key: None,
                     err: e,
                 }.into());
             }
         };
 
+        let partial = self.parse::<P>(&file_content)
+            .map_err(|e| e.redact_secret(P::meta()))?;
+        if self.strict || warnings.is_some() {
+            let keys = self.parse::<Keys>(&file_content)?;
+            let (unknown, aliases) = strict::check_keys(P::meta(), &keys);
+            if self.strict && !unknown.is_empty() {
+                let errors = unknown.into_iter().map(|k| k.into_error(&self.path)).collect();
+                return Err(Error::combine(errors).expect("at least one error"));
+            }
+            if let Some(warnings) = warnings {
+                warnings.extend(unknown.into_iter().map(|k| k.into_warning(&self.path)));
+                warnings.extend(aliases.into_iter().map(|k| k.into_warning(&self.path)));
+            }
+        }
+
+        Ok(partial)
+    }
+
+    /// Deserializes the contents of this file into `T`, according to the
+    /// file format.
+    fn parse<T: DeserializeOwned>(&self, file_content: &[u8]) -> Result<T, Error> {
         // Helper closure to create an error.
-        let error = |err| {
+        let error = |err, path, location| {
             Error::from(ErrorInner::Deserialization {
                 err,
+                path,
+                location,
                 source: Some(format!("file '{}'", self.path.display())),
+                file: Some(self.path.clone()),
             })
         };
 
         match self.format {
             #[cfg(feature = "toml")]
             FileFormat::Toml => {
-                let s = std::str::from_utf8(&file_content).map_err(|e| error(Box::new(e)))?;
-                toml::from_str(s).map_err(|e| error(Box::new(e)))
+                let s = std::str::from_utf8(file_content)
+                    .map_err(|e| error(Box::new(e), None, None))?;
+                let (res, path) = track_de_path(|| toml::from_str(s));
+                res.map_err(|e: toml::de::Error| {
+                    // The `Display` output of the TOML error already contains
+                    // a snippet, which we render ourselves.
+                    let location = e.span().map(|span| Location::from_span(s, span));
+                    error(e.message().into(), path, location)
+                })
             }
 
             #[cfg(feature = "yaml")]
-            FileFormat::Yaml => serde_yaml::from_slice(&file_content)
-                .map_err(|e| error(Box::new(e))),
+            FileFormat::Yaml => {
+                let (res, path) = track_de_path(|| serde_yaml::from_slice(file_content));
+                res.map_err(|e: serde_yaml::Error| {
+                    let location = e.location().map(|l| {
+                        let s = String::from_utf8_lossy(file_content);
+                        Location::from_span(&s, l.index()..l.index())
+                    });
+                    match path {
+                        Some(path) => {
+                            let msg = yaml_value_message(&e, &path);
+                            error(msg.into(), Some(path), location)
+                        }
+                        None => error(Box::new(e), None, location),
+                    }
+                })
+            }
 
             #[cfg(feature = "json5")]
             FileFormat::Json5 => {
-                let s = std::str::from_utf8(&file_content).map_err(|e| error(Box::new(e)))?;
-                json5::from_str(s).map_err(|e| error(Box::new(e)))
+                let s = std::str::from_utf8(file_content)
+                    .map_err(|e| error(Box::new(e), None, None))?;
+                let (res, path) = track_de_path(|| json5::from_str(s));
+                res.map_err(|e| {
+                    let json5::Error::Message { location, .. } = &e;
+                    let location = location.as_ref()
+                        .map(|l| Location::from_line_column(s, l.line, l.column));
+                    error(Box::new(e), path, location)
+                })
             }
         }
     }
 }
 
+/// Returns the message of an error deserializing the value at `path`. The
+/// `Display` output of YAML errors also contains the path and location, which
+/// we report ourselves.
+#[cfg(feature = "yaml")]
+fn yaml_value_message(e: &serde_yaml::Error, path: &str) -> String {
+    let msg = e.to_string();
+    let msg = match e.location() {
+        Some(l) => msg
+            .strip_suffix(&format!(" at line {} column {}", l.line(), l.column()))
+            .unwrap_or(&msg),
+        None => &msg,
+    };
+    msg.strip_prefix(path)
+        .and_then(|m| m.strip_prefix(": "))
+        .unwrap_or(msg)
+        .to_owned()
+}
+
+/// Returns all files in the directory `dir` that have a supported file
+/// extension, sorted lexically by file name. Subdirectories and hidden files
+/// (starting with `.`) are ignored. If the directory does not exist, `None` is
+/// returned.
+pub(crate) fn files_in_dir(dir: &Path) -> Result<Option<Vec<PathBuf>>, Error> {
+    let io_err = |err| {
+        Error::from(ErrorInner::Io { path: Some(dir.to_owned()), env_key: None, err })
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(io_err(e)),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(io_err)?;
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        let supported = path.extension().and_then(FileFormat::from_extension).is_some();
+        if !hidden && supported && path.is_file() {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(Some(files))
+}
+
 /// All file formats supported by confique.
 ///
 /// All enum variants are `#[cfg]` guarded with the respective crate feature.
@@ -2,7 +2,13 @@
 //! intended to be used directly. None of this is covered by semver! Do not use
 //! any of this directly.
 
-use crate::{error::ErrorInner, Error};
+use std::{cell::RefCell, env::VarError, fmt, path::PathBuf};
+
+use crate::{
+    env::EnvSource,
+    error::{redact_msg, ErrorInner, RedactedError},
+    Error,
+};
 
 pub fn deserialize_default<I, O>(src: I) -> Result<O, serde::de::value::Error>
 where
@@ -27,69 +33,421 @@ pub fn unwrap_or_missing_value_err<T>(value: Option<T>, path: &str) -> Result<T,
 }
 
 pub fn map_err_prefix_path<T>(res: Result<T, Error>, prefix: &str) -> Result<T, Error> {
-    res.map_err(|e| {
-        if let ErrorInner::MissingValue(path) = &*e.inner {
-            ErrorInner::MissingValue(format!("{prefix}.{path}")).into()
-        } else {
-            e
+    fn prefix_path(e: &mut Error, prefix: &str) {
+        match &mut *e.inner {
+            ErrorInner::MissingValue(path) => *path = format!("{prefix}.{path}"),
+            ErrorInner::Validation { path, .. } if path.is_empty() => *path = prefix.to_owned(),
+            ErrorInner::Validation { path, .. } => *path = format!("{prefix}.{path}"),
+            ErrorInner::Multiple(errors) => errors.iter_mut().for_each(|e| prefix_path(e, prefix)),
+            _ => {}
         }
+    }
+
+    res.map_err(|mut e| {
+        prefix_path(&mut e, prefix);
+        e
     })
 }
 
+/// Collects errors of individual fields, so that all of them can be reported
+/// at once.
+#[derive(Default)]
+pub struct ErrorCollector(Vec<Error>);
 
-macro_rules! get_env_var {
-    ($key:expr, $field:expr) => {
-        match std::env::var($key) {
-            Err(std::env::VarError::NotPresent) => return Ok(None),
-            Err(std::env::VarError::NotUnicode(_)) => {
-                let err = ErrorInner::EnvNotUnicode {
-                    key: $key.into(),
-                    field: $field.into(),
-                };
-                return Err(err.into());
+impl ErrorCollector {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Returns the value if `res` is `Ok`, otherwise stores the error.
+    pub fn collect<T>(&mut self, res: Result<T, Error>) -> Option<T> {
+        match res {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.0.push(e);
+                None
             }
-            Ok(s) => s,
         }
+    }
+
+    /// Returns all collected errors combined into one, if there are any.
+    pub fn finish(self) -> Result<(), Error> {
+        match Error::combine(self.0) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+// The validation functions take the value to validate and the user-specified
+// validation function. Errors are returned as `ErrorInner::Validation`.
+
+pub fn validate_field<T, E>(
+    value: &T,
+    path: &str,
+    validate: fn(&T) -> Result<(), E>,
+) -> Result<(), Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    validate(value).map_err(|err| ErrorInner::Validation {
+        path: path.to_owned(),
+        err: err.into(),
+    }.into())
+}
+
+pub fn validate_optional_field<T, E>(
+    value: &Option<T>,
+    path: &str,
+    validate: fn(&T) -> Result<(), E>,
+) -> Result<(), Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    match value {
+        Some(value) => validate_field(value, path, validate),
+        None => Ok(()),
+    }
+}
+
+pub fn validate_struct<T, E>(value: &T, validate: fn(&T) -> Result<(), E>) -> Result<(), Error>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    validate_field(value, "", validate)
+}
+
+// Helpers for the checks generated for constraint attributes like `min`. The
+// message is returned as error if `ok` is `false`.
+
+pub fn check(ok: bool, msg: &'static str) -> Result<(), &'static str> {
+    if ok { Ok(()) } else { Err(msg) }
+}
+
+pub fn is_one_of<T: PartialEq<U>, U>(value: &T, allowed: &[U]) -> bool {
+    allowed.iter().any(|a| value == a)
+}
+
+thread_local! {
+    /// The path of the field currently being deserialized via
+    /// `deserialize_field`.
+    static DE_PATH: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+
+    /// The path of the innermost field that failed to deserialize.
+    static DE_ERROR_PATH: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Deserializes the field `name` of a partial type with `deserialize`, keeping
+/// track of the path for error messages (see `track_de_path`).
+pub fn deserialize_field<'de, D, T>(
+    name: &'static str,
+    deserializer: D,
+    deserialize: fn(D) -> Result<T, D::Error>,
+) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    DE_PATH.with_borrow_mut(|path| path.push(name));
+    let out = deserialize(deserializer);
+    DE_PATH.with_borrow_mut(|path| {
+        if out.is_err() {
+            DE_ERROR_PATH.with_borrow_mut(|error_path| {
+                error_path.get_or_insert_with(|| path.join("."));
+            });
+        }
+        path.pop();
+    });
+    out
+}
+
+/// Runs `f`, which deserializes a partial type, and additionally returns the
+/// dotted path of the field that failed to deserialize, if any.
+///
+/// `deserialize_field` is also called outside of this function (e.g. when
+/// deserializing overrides), so the error path is reset before running `f`
+/// and cleared afterwards, to not report a stale path.
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+pub(crate) fn track_de_path<T>(f: impl FnOnce() -> T) -> (T, Option<String>) {
+    let prev_path = DE_PATH.take();
+    DE_ERROR_PATH.set(None);
+    let out = f();
+    let error_path = DE_ERROR_PATH.take();
+    DE_PATH.set(prev_path);
+    (out, error_path)
+}
+
+/// Printed by generated `Debug` impls and serialized by generated `Serialize`
+/// impls instead of the values of secret fields.
+pub struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt("<redacted>", f)
+    }
+}
+
+impl serde::Serialize for Redacted {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("<redacted>")
+    }
+}
+
+/// Used by the derive to assert that a struct with secret fields does not
+/// implement `Debug` (other than via `#[config(debug)]`): for types that
+/// implement `Debug`, both impls apply, so `<T as NotDebug<_>>::check` is
+/// ambiguous and fails to compile.
+pub trait NotDebug<A> {
+    fn check() {}
+}
+
+impl<T: ?Sized> NotDebug<()> for T {}
+impl<T: ?Sized + fmt::Debug> NotDebug<u8> for T {}
+
+pub fn push_prefixed_paths(out: &mut Vec<String>, prefix: &str, paths: Vec<String>) {
+    out.extend(paths.into_iter().map(|path| format!("{prefix}.{path}")));
+}
+
+
+/// The context in which the env keys of a struct's fields are resolved.
+///
+/// Contains a prefix that is prepended to all keys and a flag indicating
+/// whether fields without explicit key are automatically assigned one. The
+/// root context is determined by the `env_prefix` attribute of the root struct;
+/// nested fields derive their context via [`EnvScope::nested`].
+#[derive(Debug, Clone)]
+pub struct EnvScope {
+    /// The dotted path of the struct this scope belongs to, empty for the root.
+    path: String,
+    prefix: String,
+    auto: bool,
+
+    /// Whether `<KEY>_FILE` is checked for all fields (see `EnvVar`).
+    file_suffix: bool,
+}
+
+/// An env variable to load a field from.
+#[derive(Debug, Clone)]
+pub struct EnvVar {
+    key: String,
+
+    /// If `true` and `key` is not set, the variable `<key>_FILE` is checked
+    /// and, if set, the value is read from the file at that path.
+    file_suffix: bool,
+}
+
+impl EnvScope {
+    pub fn root(env_prefix: Option<&str>) -> Self {
+        Self {
+            path: String::new(),
+            prefix: env_prefix.unwrap_or("").to_owned(),
+            auto: env_prefix.is_some(),
+            file_suffix: false,
+        }
+    }
+
+    /// Enables the `_FILE` suffix check for all fields in this scope.
+    pub(crate) fn with_file_suffix(self) -> Self {
+        Self { file_suffix: true, ..self }
+    }
+
+    /// Like [`EnvScope::key`], but returns the variable to load the field
+    /// from, with the `_FILE` suffix check enabled if `file_suffix` is `true`
+    /// (i.e. the field has the `env_file_suffix` attribute) or enabled for
+    /// this whole scope.
+    pub fn var(&self, env: Option<&str>, field: &str, file_suffix: bool) -> Option<EnvVar> {
+        self.key(env, field).map(|key| EnvVar {
+            key,
+            file_suffix: file_suffix || self.file_suffix,
+        })
+    }
+
+    /// Returns the dotted path of the field `field`, e.g. `http.port`.
+    pub fn path(&self, field: &str) -> String {
+        match self.path.is_empty() {
+            true => field.to_owned(),
+            false => format!("{}.{field}", self.path),
+        }
+    }
+
+    /// Returns the full env key for the leaf field `field` with the given
+    /// (relative) key `env`, or `None` if the field cannot be loaded from env.
+    pub fn key(&self, env: Option<&str>, field: &str) -> Option<String> {
+        match env {
+            Some(key) => Some(format!("{}{key}", self.prefix)),
+            None if self.auto => Some(format!("{}{}", self.prefix, auto_env_key(field))),
+            None => None,
+        }
+    }
+
+    /// Returns the scope for the nested field `field`. `use_site_prefix` is
+    /// the `env_prefix` attribute on the field, `type_prefix` the one on the
+    /// struct of the nested type.
+    ///
+    /// Both prefixes are appended to the current prefix (in that order). If
+    /// neither is set, in automatic mode, the field name is used as additional
+    /// prefix instead (e.g. `APP_` + `LOG_`).
+    pub fn nested(
+        &self,
+        field: &str,
+        use_site_prefix: Option<&str>,
+        type_prefix: Option<&str>,
+    ) -> Self {
+        let segment = match (use_site_prefix, type_prefix) {
+            (None, None) if self.auto => format!("{}_", auto_env_key(field)),
+            (use_site, ty) => format!("{}{}", use_site.unwrap_or(""), ty.unwrap_or("")),
+        };
+
+        Self {
+            path: self.path(field),
+            prefix: format!("{}{segment}", self.prefix),
+            auto: self.auto || type_prefix.is_some(),
+            file_suffix: self.file_suffix,
+        }
+    }
+}
+
+/// Derives the env key segment from the (possibly renamed) field name:
+/// uppercased, with `-` replaced by `_` and `_` inserted at camel case word
+/// boundaries, e.g. `listenPort` -> `LISTEN_PORT`.
+fn auto_env_key(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut prev_lower = false;
+    for c in field.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(if c == '-' { '_' } else { c.to_ascii_uppercase() });
+    }
+    out
+}
+
+impl EnvVar {
+    /// Whether this variable (or `<KEY>_FILE`, if enabled) is set in `source`.
+    /// Variables with non-Unicode values count as set.
+    pub(crate) fn is_set(&self, source: &dyn EnvSource) -> bool {
+        self.set_key(source).is_some()
+    }
+
+    /// Returns the key that the value is read from: the key itself if it is
+    /// set in `source`, otherwise `<KEY>_FILE` if enabled and set. Like in
+    /// [`get_env_var`], variables with non-Unicode values count as set.
+    pub(crate) fn set_key(&self, source: &dyn EnvSource) -> Option<String> {
+        let is_set = |key: &str| !matches!(source.var(key), Err(VarError::NotPresent));
+        if is_set(&self.key) {
+            return Some(self.key.clone());
+        }
+
+        let file_key = format!("{}_FILE", self.key);
+        (self.file_suffix && is_set(&file_key)).then_some(file_key)
+    }
+}
+
+/// Returns `var` if it is set in `source`, and otherwise the first of the
+/// alias keys (`#[config(alias_env = "...")]`, relative to the scope's prefix)
+/// that is set. If none of them is set, `var` is returned.
+pub fn env_var_with_aliases(
+    source: &dyn EnvSource,
+    scope: &EnvScope,
+    var: Option<EnvVar>,
+    aliases: &[&str],
+) -> Option<EnvVar> {
+    let var = var?;
+    if var.is_set(source) {
+        return Some(var);
+    }
+
+    aliases.iter()
+        .map(|alias| EnvVar {
+            key: format!("{}{alias}", scope.prefix),
+            file_suffix: var.file_suffix,
+        })
+        .find(|alias| alias.is_set(source))
+        .or(Some(var))
+}
+
+/// Returns the value of `var` and the key it was actually read from, or
+/// `None` if it is not set. If enabled and `<KEY>` is not set, `<KEY>_FILE`
+/// is checked and the value is read from the file it points to, with trailing
+/// newlines removed.
+fn get_env_var(
+    source: &dyn EnvSource,
+    var: EnvVar,
+    field: &str,
+) -> Result<Option<(String, String)>, Error> {
+    let not_unicode = |key: String| -> Error {
+        ErrorInner::EnvNotUnicode { key, field: field.into() }.into()
+    };
+
+    match source.var(&var.key) {
+        Ok(s) => return Ok(Some((var.key, s))),
+        Err(VarError::NotUnicode(_)) => return Err(not_unicode(var.key)),
+        Err(VarError::NotPresent) if !var.file_suffix => return Ok(None),
+        Err(VarError::NotPresent) => {}
+    }
+
+    let file_key = format!("{}_FILE", var.key);
+    let path = match source.var(&file_key) {
+        Ok(path) => PathBuf::from(path),
+        Err(VarError::NotPresent) => return Ok(None),
+        Err(VarError::NotUnicode(_)) => return Err(not_unicode(file_key)),
     };
+
+    match std::fs::read_to_string(&path) {
+        Ok(s) => Ok(Some((file_key, s.trim_end_matches(['\n', '\r']).to_owned()))),
+        Err(err) => Err(ErrorInner::Io { path: Some(path), env_key: Some(file_key), err }.into()),
+    }
 }
 
+// All these functions take the variable as returned by `EnvScope::var` and
+// return `Ok(None)` if it's `None`. If `secret` is `true`, error messages do
+// not contain any details that might include the value.
+
 pub fn from_env<'de, T: serde::Deserialize<'de>>(
-    key: &str,
+    source: &dyn EnvSource,
+    var: Option<EnvVar>,
     field: &str,
+    secret: bool,
 ) -> Result<Option<T>, Error> {
-    from_env_with_deserializer(key, field, |de| T::deserialize(de))
+    from_env_with_deserializer(source, var, field, secret, |de| T::deserialize(de))
 }
 
 pub fn from_env_with_parser<T, E: std::error::Error + Send + Sync + 'static>(
-    key: &str,
+    source: &dyn EnvSource,
+    var: Option<EnvVar>,
     field: &str,
+    secret: bool,
     parse: fn(&str) -> Result<T, E>,
 ) -> Result<Option<T>, Error> {
-    let v = get_env_var!(key, field);
+    let Some(var) = var else { return Ok(None) };
+    let Some((key, v)) = get_env_var(source, var, field)? else { return Ok(None) };
     parse(&v)
         .map(Some)
         .map_err(|err| {
-            ErrorInner::EnvParseError {
-                field: field.to_owned(),
-                key: key.to_owned(),
-                err: Box::new(err),
-            }.into()
+            let err: Box<dyn std::error::Error + Send + Sync> = match secret {
+                true => Box::new(RedactedError),
+                false => Box::new(err),
+            };
+            ErrorInner::EnvParseError { field: field.to_owned(), key, err }.into()
         })
 }
 
 pub fn from_env_with_deserializer<T>(
-    key: &str,
+    source: &dyn EnvSource,
+    var: Option<EnvVar>,
     field: &str,
+    secret: bool,
     deserialize: fn(crate::env::Deserializer) -> Result<T, crate::env::DeError>,
 ) -> Result<Option<T>, Error> {
-    let s = get_env_var!(key, field);
+    let Some(var) = var else { return Ok(None) };
+    let Some((key, s)) = get_env_var(source, var, field)? else { return Ok(None) };
 
     match deserialize(crate::env::Deserializer::new(s)) {
         Ok(v) => Ok(Some(v)),
         Err(e) => Err(ErrorInner::EnvDeserialization {
-            key: key.into(),
+            key,
             field: field.into(),
-            msg: e.0,
+            msg: redact_msg(e.0, secret),
         }.into()),
     }
 }
@@ -3,10 +3,13 @@
 
 use std::fmt::{self, Write};
 
+use serde::Serialize;
+
 use crate::{
-    Config,
+    Config, Error, Provenance,
     template::{self, Formatter},
     meta::Expr,
+    ser::Value,
 };
 
 
@@ -99,6 +102,41 @@ pub fn template<C: Config>(options: FormatOptions) -> String {
     out.finish()
 }
 
+/// Formats the given configuration as JSON5, e.g. to show the effective
+/// configuration of your application.
+///
+/// Works like [`toml::to_string`][crate::toml::to_string], see its
+/// documentation for more information.
+pub fn to_string<C: Config + Serialize>(
+    config: &C,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    format_values(config, None, options)
+}
+
+/// Like [`to_string`], but additionally states the source of each value in a
+/// comment above it. See
+/// [`toml::to_string_with_provenance`][crate::toml::to_string_with_provenance]
+/// for more information.
+pub fn to_string_with_provenance<C: Config + Serialize>(
+    config: &C,
+    provenance: &Provenance,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    format_values(config, Some(provenance), options)
+}
+
+fn format_values<C: Config + Serialize>(
+    config: &C,
+    provenance: Option<&Provenance>,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    let values = Value::from_serialize(config)?;
+    let mut out = Json5Formatter::new(&options);
+    template::format_values(&C::META, &values, provenance, &mut out, options.general)?;
+    Ok(out.finish())
+}
+
 struct Json5Formatter {
     indent: u8,
     buffer: String,
@@ -140,16 +178,28 @@ impl Formatter for Json5Formatter {
     }
 
     fn disabled_field(&mut self, name: &str, value: Option<&'static Expr>) {
+        let name = PrintKey(name);
         match value.map(PrintExpr) {
             None => self.comment(format_args!("{name}: ,")),
             Some(v) => self.comment(format_args!("{name}: {v},")),
         };
     }
 
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), String> {
+        let value = json5::to_string(value).map_err(|e| e.to_string())?;
+        self.emit_indentation();
+        writeln!(self.buffer, "{}: {value},", PrintKey(name)).unwrap();
+        Ok(())
+    }
+
+    fn redacted_field(&mut self, name: &'static str) {
+        self.comment(format_args!("{}: <redacted>,", PrintKey(name)));
+    }
+
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]) {
         doc.iter().for_each(|doc| self.comment(doc));
         self.emit_indentation();
-        writeln!(self.buffer, "{name}: {{").unwrap();
+        writeln!(self.buffer, "{}: {{", PrintKey(name)).unwrap();
         self.depth += 1;
     }
 
@@ -175,6 +225,23 @@ impl Formatter for Json5Formatter {
     }
 }
 
+/// Helper to emit a field name into JSON5, quoted if it is not a valid
+/// identifier (e.g. due to `#[config(rename = "...")]`).
+struct PrintKey<'a>(&'a str);
+
+impl fmt::Display for PrintKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let is_ident = self.0.chars().next().is_some_and(|c| !c.is_ascii_digit())
+            && self.0.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+        match is_ident {
+            true => self.0.fmt(f),
+            false => json5::to_string(&self.0)
+                .expect("string serialization to JSON5 failed")
+                .fmt(f),
+        }
+    }
+}
+
 /// Helper to emit `meta::Expr` into JSON5.
 struct PrintExpr(&'static Expr);
 
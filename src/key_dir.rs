@@ -0,0 +1,98 @@
+//! Loading configuration from a directory with one file per value, like
+//! mounted Kubernetes ConfigMaps and secrets.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    dotted,
+    error::{redact_msg, ErrorInner},
+    Config, Error, Partial, ValueSource, Warning,
+};
+
+
+/// Loads all files in `dir` whose name is the dotted path of a leaf field,
+/// with `__` instead of `.` also being accepted. Other files, hidden files and
+/// subdirectories are ignored. Trailing newlines are stripped from the file
+/// contents, which are then deserialized like environment variables. If the
+/// directory does not exist, an empty partial is returned. If `strict` is
+/// set, files not corresponding to any leaf field are an error instead. Both
+/// of these cases are otherwise added to `warnings`.
+///
+/// Also returns a map from path to the file that set it.
+pub(crate) fn load<C: Config>(
+    dir: &Path,
+    strict: bool,
+    warnings: &mut Vec<Warning>,
+) -> Result<(C::Partial, HashMap<String, PathBuf>), Error> {
+    let io_err = |path: &Path, err| {
+        Error::from(ErrorInner::Io { path: Some(path.to_owned()), env_key: None, err })
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            warnings.push(Warning::MissingFile { path: dir.to_owned() });
+            return Ok((C::Partial::empty(), HashMap::new()));
+        }
+        Err(e) => return Err(io_err(dir, e)),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| io_err(dir, e))?;
+        let Some(name) = entry.file_name().to_str().map(ToOwned::to_owned) else {
+            continue;
+        };
+        let path = entry.path();
+        if !name.starts_with('.') && path.is_file() {
+            files.push((name, path));
+        }
+    }
+    files.sort();
+
+    let leaves = dotted::leaves(&C::META);
+    let mut partial = C::Partial::empty();
+    let mut origins = HashMap::new();
+    let mut unknown = Vec::new();
+    for (name, file) in files {
+        let path = name.replace("__", ".");
+        let Some(leaf) = dotted::find_leaf(&leaves, &path) else {
+            let suggestion = dotted::suggestion(leaves.iter().map(|l| &*l.path), &path)
+                .map(ToOwned::to_owned);
+            if strict {
+                unknown.push(Error::from(ErrorInner::UnknownKey {
+                    path,
+                    suggestion,
+                    source: ValueSource::KeyFile(file),
+                }));
+            } else {
+                warnings.push(Warning::UnknownKey {
+                    path,
+                    suggestion,
+                    source: ValueSource::KeyFile(file),
+                });
+            }
+            continue;
+        };
+
+        let content = fs::read_to_string(&file).map_err(|e| io_err(&file, e))?;
+        let value = content.trim_end_matches(['\n', '\r']).to_owned();
+        let layer = dotted::partial_from_path::<C::Partial>(&leaf.path, value)
+            .map_err(|e| ErrorInner::KeyFileDeserialization {
+                field: leaf.path.clone(),
+                path: file.clone(),
+                msg: redact_msg(e.0, leaf.secret),
+            })?;
+        partial = layer.with_fallback(partial);
+        origins.insert(leaf.path.clone(), file);
+    }
+
+    if let Some(e) = Error::combine(unknown) {
+        return Err(e);
+    }
+
+    Ok((partial, origins))
+}
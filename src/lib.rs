@@ -168,19 +168,36 @@
 //! - `yaml`: enables YAML support and adds the `serde_yaml` dependency.
 //! - `json5`: enables JSON5 support and adds the `json5` dependency.
 
+// The attribute docs of the derive use deeper indented list items.
+#![allow(clippy::doc_overindented_list_items)]
+
 use serde::Deserialize;
 
 #[doc(hidden)]
 pub mod internal;
 
+mod args;
 mod builder;
+mod credentials;
+mod dotted;
 pub mod env;
 mod error;
+mod key_dir;
 pub mod meta;
+mod overrides;
+mod provenance;
+pub mod schema;
+mod warning;
 
 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
 mod file;
 
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+mod ser;
+
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+mod strict;
+
 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
 mod template;
 
@@ -200,7 +217,9 @@ mod test_utils;
 pub use serde;
 pub use self::{
     builder::Builder,
-    error::Error,
+    error::{Error, ErrorKind, Location, SourceLayer},
+    provenance::{Provenance, ValueSource},
+    warning::Warning,
 };
 
 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
@@ -264,7 +283,11 @@ pub use crate::{
 /// - **Nested fields**: they have to be annotated with `#[config(nested)]` and
 ///   contain a nested configuration object. The type of this field must
 ///   implement `Config`. As implied by the previous statement, `Option<_>` as
-///   type for nested fields is not allowed.
+///   type for nested fields is not allowed. Nested fields can additionally
+///   have an `#[config(nested, env_prefix = "PREFIX_")]` attribute, which
+///   prepends the prefix to the env keys of all fields inside the nested
+///   configuration. This allows using the same type multiple times, e.g. for a
+///   primary and replica database, with different environment variables.
 ///
 /// - **Leaf fields**: all fields *not* annotated with `#[config(nested)]`,
 ///   these contain your actual values. The type of such a field has to
@@ -316,6 +339,66 @@ pub use crate::{
 ///   field. In [`Partial::from_env`], the variable is checked and
 ///   deserialized into the field if present.
 ///
+/// - **`#[config(env_file_suffix)]`**: if the field's environment variable
+///   `KEY` is not set, `KEY_FILE` is checked instead. If that is set, the
+///   value is read from the file at the path it contains (with trailing
+///   newlines removed) and treated exactly like the value of `KEY`. This is a
+///   common way of passing secrets in Docker. Requires an env key (via `env`
+///   or `env_prefix`). Can be enabled for all fields with
+///   [`Builder::env_file_suffix`].
+///
+/// - **`#[config(credential = "name")]`**: assigns a systemd credential to
+///   this field. With [`Builder::systemd_credentials`], the value is loaded
+///   from the file `name` in `$CREDENTIALS_DIRECTORY` if it exists. Useful to
+///   keep secrets out of environment variables and configuration files.
+///
+/// - **`#[config(validate = path::to::function)]`**: function called by
+///   [`Config::from_partial`] to validate the value after all values have been
+///   loaded. Function needs signature `fn(&T) -> Result<(), E>` where `T` is
+///   the field's type (without `Option<_>`, as the function is only called
+///   for present values) and `E` can be converted into
+///   `Box<dyn std::error::Error + Send + Sync>`, e.g. `&'static str` or
+///   `String`. If validation fails, an error containing the path of the field
+///   and the message is returned.
+///
+/// - **`#[config(min = 1, max = 65535)]`**, **`#[config(one_of = ["a", "b"])]`**
+///   and **`#[config(non_empty)]`**: declarative constraints, checked by
+///   [`Config::from_partial`] like `validate` (and before it). `min` and `max`
+///   take integer or float literals and can be used separately, `one_of` an
+///   array of literals the value is compared with, and `non_empty` requires
+///   the type to have an `is_empty` method (e.g. `String` or `Vec<_>`). The
+///   constraints are mentioned in templates and available as
+///   [`meta::FieldKind::Leaf::constraints`].
+///
+/// - **`#[config(secret)]`**: marks the value as secret (e.g. a password or
+///   token). Its value is never included in error messages (e.g. when it fails
+///   to deserialize), in the provenance (see [`Builder::overrides`]) or in the
+///   `Debug` output generated by the `debug` struct attribute, where
+///   `"<redacted>"` is printed instead. Structs with secret fields must not
+///   implement `Debug` otherwise (e.g. via `#[derive(Debug)]`), which results
+///   in a compile error. Also available as [`meta::Field::secret`].
+///
+/// - **`#[config(rename = "name")]`**: the name of this field in
+///   configuration files, dotted paths (e.g. in error messages),
+///   [`meta::Field::name`] and templates. The automatically assigned env key
+///   (see the `env_prefix` struct attribute) is derived from it as well.
+///
+/// - **`#[config(alias = "old_name")]`**: an old name of the field that is
+///   still accepted in configuration files. Can be specified multiple times
+///   and also on nested fields. Using an alias results in a
+///   [`Warning::DeprecatedAlias`] (see [`Builder::load_with_warnings`]) and
+///   aliases are mentioned in templates.
+///
+/// - **`#[config(alias_env = "OLD_KEY")]`**: an old environment variable key
+///   that is checked if the field's key is not set. Prefixed like `env`.
+///   Can be specified multiple times and requires an env key (via `env` or
+///   `env_prefix`). Like `alias`, using it results in a warning.
+///
+/// - **`#[config(deprecated = "use `x` instead")]`**: marks the field as
+///   deprecated. Setting it (or, for nested fields, any value inside it) in
+///   any source results in a [`Warning::Deprecated`]. The message is also
+///   included in templates.
+///
 /// - **`#[config(deserialize_with = path::to::function)]`**: like
 ///   [serde's `deserialize_with` attribute][serde-deser].
 ///
@@ -326,14 +409,54 @@ pub use crate::{
 ///     the field.. Can only be present if the `env` attribute is present. Also
 ///     see [`env::parse`].
 ///
-/// There are also the following attributes on the struct itself:
+/// There are also the following attributes on the struct itself (multiple
+/// can be combined in one attribute, e.g. `#[config(debug, serialize)]`):
 ///
 /// - **`#[config(partial_attr(...))]`: specify attributes that should be
 ///     attached to the partial struct definition. For example,
 ///     `#[config(partial_attr(derive(Clone)))]` can be used to make the partial
-///     type implement `Clone`.
+///     type implement `Clone`. Serde attributes changing keys (like
+///     `serde(rename_all = ...)`) are not reflected in [`Config::META`] and
+///     thus not supported by strict mode ([`File::strict`]).
+///
+/// - **`#[config(validate = path::to::function)]`**: like the field attribute,
+///   but the function is passed the whole struct (`fn(&Self) -> Result<(),
+///   E>`) and is called after all fields have been validated. Useful to check
+///   relationships between fields.
+///
+/// - **`#[config(rename_all = "kebab-case")]`**: renames all fields (that do
+///   not have a `rename` attribute) according to the given case convention,
+///   like [serde's `rename_all`][serde-rename-all]. Prefer this over
+///   `partial_attr(serde(rename_all = ...))`, which only affects
+///   deserialization, but not templates, error messages or env keys.
+///   Automatically assigned env keys are derived from the renamed names, with
+///   `-` replaced by `_` and word boundaries preserved, e.g. `listenPort` is
+///   loaded from `LISTEN_PORT`.
+///
+/// - **`#[config(debug)]`**: implements `Debug` for the struct and its partial
+///   type, printing `"<redacted>"` instead of the values of `secret` fields.
+///   Use this instead of `#[derive(Debug)]`, which is rejected for structs with
+///   secret fields. Nested configuration types need to implement `Debug` as
+///   well (their partial types, too).
+///
+/// - **`#[config(serialize)]`**: implements `serde::Serialize` for the
+///   struct, using the same field names as [`Config::META`] (i.e. respecting
+///   `rename` and `rename_all`). Required for `toml::to_string` and the other
+///   `to_string` functions. All field types (including nested configuration
+///   types) need to implement `Serialize`. The values of `secret` fields are
+///   serialized as `"<redacted>"` (or as `None` for unset optional fields).
+///
+/// - **`#[config(env_prefix = "PREFIX_")]`**: automatically assigns an
+///   environment variable key to all leaf fields of this struct *and all
+///   nested structs*. The key is derived from the path to the field: e.g. with
+///   `env_prefix = "APP_"`, the field `log.file` is loaded from `APP_LOG_FILE`.
+///   The prefix is also prepended to keys specified with `env = "..."`, so
+///   `#[config(env = "PORT")]` results in `APP_PORT`. If a nested struct has
+///   its own `env_prefix`, it is used instead of the field name, e.g.
+///   `APP_` + `DB_` + `HOST`. See [`Partial::from_env`].
 ///
 /// [serde-deser]: https://serde.rs/field-attrs.html#deserialize_with
+/// [serde-rename-all]: https://serde.rs/container-attrs.html#rename_all
 ///
 /// ## Special types for leaf fields
 ///
@@ -426,8 +549,9 @@ pub trait Config: Sized {
 
     /// Tries to create `Self` from a potentially partial object.
     ///
-    /// If any required values are not defined in `partial`, an [`Error`] is
-    /// returned.
+    /// If any required values are not defined in `partial` or any validation
+    /// fails, an [`Error`] is returned. It contains all such problems, not
+    /// just the first one (see [`Error::errors`]).
     fn from_partial(partial: Self::Partial) -> Result<Self, Error>;
 
     /// Convenience builder to configure, load and merge multiple configuration
@@ -510,13 +634,50 @@ pub trait Partial: for<'de> Deserialize<'de> {
     fn default_values() -> Self;
 
     /// Loads values from environment variables. This is only relevant for
-    /// fields annotated with `#[config(env = "...")]`: all fields not
-    /// annotated `env` will be `None`.
+    /// fields annotated with `#[config(env = "...")]` or fields of a struct
+    /// annotated with `#[config(env_prefix = "...")]`: all other fields will be
+    /// `None`.
     ///
     /// If the env variable corresponding to a field is not set, that field is
     /// `None`. If it is set but it failed to deserialize into the target type,
-    /// an error is returned.
-    fn from_env() -> Result<Self, Error>;
+    /// an error is returned, containing all variables that failed.
+    ///
+    /// This reads the environment of the current process. To load from a
+    /// different set of variables, use [`Partial::from_env_source`].
+    fn from_env() -> Result<Self, Error> {
+        Self::from_env_source(&env::ProcessEnv)
+    }
+
+    /// Like [`Partial::from_env`], but loads variables from the given source
+    /// instead of the environment of the current process. This is useful for
+    /// tests, as those do not need to modify the global process environment
+    /// and can run in parallel.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use confique::{Config, Partial};
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(env = "PORT")]
+    ///     port: u16,
+    /// }
+    ///
+    /// let vars = HashMap::from([("PORT".to_string(), "8080".to_string())]);
+    /// let partial = <Conf as Config>::Partial::from_env_source(&vars)?;
+    /// assert_eq!(partial.port, Some(8080));
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    fn from_env_source(source: &dyn env::EnvSource) -> Result<Self, Error>;
+
+    /// Like [`Partial::from_env`], but loads from the given env source and
+    /// resolves env keys in the given scope. Used by the code generated for
+    /// nested fields. Not covered by semver.
+    #[doc(hidden)]
+    fn from_env_scoped(
+        source: &dyn env::EnvSource,
+        scope: &internal::EnvScope,
+    ) -> Result<Self, Error>;
 
     /// Combines two partial configuration objects. `self` has a higher
     /// priority; missing values in `self` are filled with values in `fallback`,
@@ -531,4 +692,14 @@ pub trait Partial: for<'de> Deserialize<'de> {
     /// configuration are set. If this returns `true`, `Config::from_partial`
     /// will not return an error.
     fn is_complete(&self) -> bool;
+
+    /// Returns the dotted paths (e.g. `http.port`) of all leaf values that are
+    /// set (i.e. not `None`), in field definition order.
+    fn set_paths(&self) -> Vec<String>;
+
+    /// Returns the metadata of the configuration type this is the partial
+    /// type of. Used to detect unknown keys in strict mode. Not covered by
+    /// semver.
+    #[doc(hidden)]
+    fn meta() -> &'static meta::Meta;
 }
@@ -3,6 +3,8 @@
 
 use core::fmt;
 
+use crate::internal::EnvScope;
+
 // TODO: having all these fields public make me uncomfortable. For now it's
 // fine, but before reaching 1.0 I need to figure out how to allow future
 // additions without breaking stuff.
@@ -16,24 +18,73 @@ pub struct Meta {
     /// Doc comments.
     pub doc: &'static [&'static str],
 
+    /// The value of the `#[config(env_prefix = "...")]` attribute, if set.
+    pub env_prefix: Option<&'static str>,
+
     pub fields: &'static [Field],
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Field {
+    /// The name of the field as used in configuration files and dotted paths,
+    /// i.e. after applying `#[config(rename = "...")]` or the struct's
+    /// `#[config(rename_all = "...")]`.
     pub name: &'static str,
     pub doc: &'static [&'static str],
+
+    /// Whether the field is marked with `#[config(secret)]`, i.e. its value
+    /// must never be printed. Always `false` for nested fields.
+    pub secret: bool,
+
+    /// Old names of this field that are still accepted in files
+    /// (`#[config(alias = "...")]`). Using them results in a warning.
+    pub aliases: &'static [&'static str],
+
+    /// The message of the `#[config(deprecated = "...")]` attribute, if set.
+    /// Setting a deprecated value results in a warning.
+    pub deprecated: Option<&'static str>,
     pub kind: FieldKind,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FieldKind {
     Leaf {
+        /// The env key of this field (`#[config(env = "...")]`). If the struct
+        /// or a parent struct has an `env_prefix`, that is prepended to this
+        /// key, and fields without `env` are assigned a key derived from their
+        /// name. Use [`Meta::env_keys`] to get the fully resolved keys.
         env: Option<&'static str>,
+
+        /// Old env keys that are checked if the variable `env` is not set
+        /// (`#[config(alias_env = "...")]`). Like `env`, these are prefixed
+        /// with the `env_prefix` in effect.
+        env_aliases: &'static [&'static str],
+
+        /// Whether the `#[config(env_file_suffix)]` attribute is set, i.e.
+        /// whether the value can also be read from the file whose path is
+        /// specified in `<KEY>_FILE`.
+        env_file_suffix: bool,
+
+        /// The name of the systemd credential this value can be loaded from
+        /// (`#[config(credential = "...")]`).
+        credential: Option<&'static str>,
+
+        /// Constraints on the value specified via attributes like
+        /// `#[config(min = 1)]`. They are checked when loading.
+        constraints: &'static [Constraint],
+
+        /// The type of the value, as far as it can be inferred from the field
+        /// type. For `Option<T>` fields, this is the type of `T`.
+        ty: Type,
         kind: LeafKind,
     },
     Nested {
         meta: &'static Meta,
+
+        /// The value of the `#[config(env_prefix = "...")]` attribute on the
+        /// nested field, if set. It is prepended to the env keys of all fields
+        /// in `meta`.
+        env_prefix: Option<&'static str>,
     },
 }
 
@@ -45,6 +96,60 @@ pub enum LeafKind {
     Optional,
 }
 
+/// The type of a leaf value in configuration files, inferred from the Rust
+/// type of the field by the derive macro.
+///
+/// Derive macros cannot resolve types, so this is a heuristic based on the
+/// *name* of the type's last path segment (e.g. `u16`, `Vec<_>` or
+/// `std::path::PathBuf`), ignoring the rest of the path. This has some
+/// consequences:
+///
+/// - Type aliases and custom types result in [`Type::Any`].
+/// - A custom type with the same name as a recognized one (e.g. your own
+///   `SocketAddr` or `Vec<T>`) is reported as the latter, regardless of how
+///   it deserializes.
+/// - Fields with `deserialize_with` always have type `Any`.
+///
+/// So treat this as a hint (e.g. for documentation or schema generation), not
+/// as a guarantee about what values are accepted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Type {
+    Bool,
+
+    /// An integer, with `min` and `max` being the limits of the Rust type
+    /// (e.g. `0` and `65535` for `u16`).
+    Integer { min: Integer, max: Integer },
+    Float,
+
+    /// A string, e.g. `String`, but also `PathBuf` or `IpAddr`.
+    String,
+
+    /// A list (e.g. `Vec<T>`) with the given item type.
+    Array(&'static Type),
+
+    /// A map with string keys (e.g. `HashMap<String, T>`) with the given value
+    /// type.
+    Map(&'static Type),
+
+    /// The type is not known.
+    Any,
+}
+
+/// A constraint on the value of a leaf field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Constraint {
+    /// `#[config(min = ...)]`: the value must be at least this.
+    Min(Expr),
+    /// `#[config(max = ...)]`: the value must be at most this.
+    Max(Expr),
+    /// `#[config(one_of = [...])]`: the value must be one of these.
+    OneOf(&'static [Expr]),
+    /// `#[config(non_empty)]`: the value must not be empty.
+    NonEmpty,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
 #[serde(untagged)]
 #[non_exhaustive]
@@ -101,6 +206,120 @@ pub enum Integer {
 }
 
 
+impl Meta {
+    /// Returns the dotted path and full env key (including all prefixes) of
+    /// every leaf field that can be loaded from an environment variable, in
+    /// field definition order. Keys are resolved for this type being the root
+    /// configuration, so each use site of a nested type is resolved
+    /// separately.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(nested)]
+    ///     primary: DbConf,
+    ///
+    ///     #[config(nested, env_prefix = "REPLICA_")]
+    ///     replica: DbConf,
+    /// }
+    ///
+    /// #[derive(Config)]
+    /// struct DbConf {
+    ///     #[config(env = "DB_HOST")]
+    ///     host: String,
+    /// }
+    ///
+    /// fn main() {
+    ///     assert_eq!(Conf::META.env_keys(), [
+    ///         ("primary.host".to_string(), "DB_HOST".to_string()),
+    ///         ("replica.host".to_string(), "REPLICA_DB_HOST".to_string()),
+    ///     ]);
+    /// }
+    /// ```
+    pub fn env_keys(&self) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        self.for_each_leaf(|path, field, scope| {
+            if let Some(key) = field.env_key(scope) {
+                out.push((path.to_owned(), key));
+            }
+        });
+        out
+    }
+
+    /// Returns whether the value at the dotted `path` (or a value inside it)
+    /// belongs to a field marked with `#[config(secret)]`. Aliases of the
+    /// field are treated like its name.
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    pub(crate) fn is_secret(&self, path: &str) -> bool {
+        let mut out = false;
+        self.for_each_leaf(|leaf_path, field, _| {
+            if !field.secret {
+                return;
+            }
+            let parent = leaf_path.rsplit_once('.').map(|(parent, _)| format!("{parent}."));
+            let parent = parent.as_deref().unwrap_or("");
+            out |= std::iter::once(field.name)
+                .chain(field.aliases.iter().copied())
+                .any(|name| {
+                    let p = format!("{parent}{name}");
+                    path == p || path.starts_with(&format!("{p}."))
+                });
+        });
+        out
+    }
+
+    /// Calls `f` for every leaf field (recursing into nested fields) with the
+    /// dotted path to that field, e.g. `http.port`, and the scope in which its
+    /// env key is resolved. Fields are visited in definition order.
+    pub(crate) fn for_each_leaf<'a>(&'a self, f: impl FnMut(&str, &'a Field, &EnvScope)) {
+        self.for_each_leaf_in(&EnvScope::root(self.env_prefix), f);
+    }
+
+    /// Like [`Meta::for_each_leaf`], but starting with the given root scope.
+    pub(crate) fn for_each_leaf_in<'a>(
+        &'a self,
+        root: &EnvScope,
+        mut f: impl FnMut(&str, &'a Field, &EnvScope),
+    ) {
+        fn imp<'a>(
+            meta: &'a Meta,
+            prefix: &str,
+            scope: &EnvScope,
+            f: &mut dyn FnMut(&str, &'a Field, &EnvScope),
+        ) {
+            for field in meta.fields {
+                let path = if prefix.is_empty() {
+                    field.name.to_owned()
+                } else {
+                    format!("{prefix}.{}", field.name)
+                };
+
+                match &field.kind {
+                    FieldKind::Leaf { .. } => f(&path, field, scope),
+                    FieldKind::Nested { meta, env_prefix } => {
+                        let scope = scope.nested(field.name, *env_prefix, meta.env_prefix);
+                        imp(meta, &path, &scope, f);
+                    }
+                }
+            }
+        }
+
+        imp(self, "", root, &mut f);
+    }
+}
+
+impl Field {
+    /// Returns the full env key of this leaf field in the given scope.
+    pub(crate) fn env_key(&self, scope: &EnvScope) -> Option<String> {
+        match self.kind {
+            FieldKind::Leaf { env, .. } => scope.key(env, self.name),
+            FieldKind::Nested { .. } => None,
+        }
+    }
+}
+
 impl fmt::Display for Float {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
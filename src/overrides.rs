@@ -0,0 +1,57 @@
+//! Loading single configuration values from `path=value` overrides.
+
+use std::collections::HashMap;
+
+use crate::{
+    dotted,
+    error::{redact_msg, ErrorInner},
+    Config, Error, Partial,
+};
+
+
+/// Parses the given `path=value` strings into a partial configuration. Paths
+/// have to refer to existing leaf fields. Values are deserialized like
+/// environment variables. If the same value is specified multiple times, the
+/// last one wins.
+///
+/// Also returns a map from path to the override string that set it.
+pub(crate) fn load<C: Config>(
+    overrides: Vec<String>,
+) -> Result<(C::Partial, HashMap<String, String>), Error> {
+    let leaves = dotted::leaves(&C::META);
+    let mut partial = C::Partial::empty();
+    let mut origins = HashMap::new();
+    for s in overrides {
+        let invalid = |msg: String| -> Error {
+            ErrorInner::InvalidOverride { value: s.clone(), msg }.into()
+        };
+
+        let (name, value) = s.split_once('=')
+            .ok_or_else(|| invalid("expected `path=value`".into()))?;
+        let name = name.trim();
+        let leaf = dotted::find_leaf(&leaves, name).ok_or_else(|| {
+            invalid(match dotted::suggestion(dotted::paths(&leaves), name) {
+                Some(path) => format!("unknown configuration value `{name}` \
+                    (did you mean `{path}`?)"),
+                None => format!("unknown configuration value `{name}`"),
+            })
+        })?;
+
+        // The override string is used in errors and the provenance, so the
+        // value of secret fields must be removed.
+        let printable = match leaf.secret {
+            true => format!("{name}=<redacted>"),
+            false => s.clone(),
+        };
+        let layer = dotted::partial_from_path::<C::Partial>(&leaf.path, value.to_owned())
+            .map_err(|e| ErrorInner::OverrideDeserialization {
+                field: leaf.path.clone(),
+                value: printable.clone(),
+                msg: redact_msg(e.0, leaf.secret),
+            })?;
+        partial = layer.with_fallback(partial);
+        origins.insert(leaf.path.clone(), printable);
+    }
+
+    Ok((partial, origins))
+}
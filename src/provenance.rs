@@ -0,0 +1,90 @@
+//! Tracking where configuration values came from.
+
+use std::{fmt, path::PathBuf};
+
+
+/// Records which layer supplied each value of a loaded configuration.
+///
+/// Obtained via [`Builder::load_with_provenance`][crate::Builder::load_with_provenance].
+/// Values are identified by their dotted path, e.g. `log.file`, in the same
+/// format as used in error messages. Optional values that were not set by any
+/// layer do not have an entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// Entries in field definition order.
+    entries: Vec<(String, ValueSource)>,
+}
+
+impl Provenance {
+    pub(crate) fn new(entries: Vec<(String, ValueSource)>) -> Self {
+        Self { entries }
+    }
+
+    /// Returns the source of the value with the given dotted path, or `None`
+    /// if that value was not set by any layer (or the path does not exist).
+    pub fn get(&self, path: &str) -> Option<&ValueSource> {
+        self.entries.iter().find(|(p, _)| p == path).map(|(_, source)| source)
+    }
+
+    /// Iterates over all `(path, source)` pairs in field definition order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ValueSource)> {
+        self.entries.iter().map(|(path, source)| (&**path, source))
+    }
+}
+
+/// The source that supplied a single configuration value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValueSource {
+    /// The value was loaded from the configuration file at this path.
+    File(PathBuf),
+
+    /// The value was loaded from the environment variable with this key.
+    Env { key: String },
+
+    /// The value was loaded from the variable `key` defined in the dotenv
+    /// file at `path` (see [`Builder::env_file`][crate::Builder::env_file]).
+    EnvFile { path: PathBuf, key: String },
+
+    /// The value was loaded from this file in a directory added via
+    /// [`Builder::key_dir`][crate::Builder::key_dir].
+    KeyFile(PathBuf),
+
+    /// The value was loaded from the systemd credential with this name (see
+    /// [`Builder::systemd_credentials`][crate::Builder::systemd_credentials]).
+    Credential { name: String },
+
+    /// The value was loaded from the command line argument with this name,
+    /// e.g. `--http.port` (see [`Builder::args`][crate::Builder::args]).
+    Arg { name: String },
+
+    /// The value was set by the override `path=value` string (see
+    /// [`Builder::overrides`][crate::Builder::overrides]).
+    Override { value: String },
+
+    /// The value was set in a layer added via
+    /// [`Builder::preloaded`][crate::Builder::preloaded]. `index` counts only
+    /// preloaded layers, starting at 0 for the first `preloaded` call.
+    Preloaded { index: usize },
+
+    /// The value is the default specified via `#[config(default = ...)]`.
+    Default,
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "file '{}'", path.display()),
+            Self::Env { key } => write!(f, "environment variable `{key}`"),
+            Self::EnvFile { path, key } => {
+                write!(f, "variable `{key}` in env file '{}'", path.display())
+            }
+            Self::KeyFile(path) => write!(f, "key file '{}'", path.display()),
+            Self::Credential { name } => write!(f, "systemd credential `{name}`"),
+            Self::Arg { name } => write!(f, "command line argument `{name}`"),
+            Self::Override { value } => write!(f, "override `{value}`"),
+            Self::Preloaded { index } => write!(f, "preloaded layer #{index}"),
+            Self::Default => f.write_str("default value"),
+        }
+    }
+}
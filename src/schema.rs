@@ -0,0 +1,378 @@
+//! Generating a [JSON Schema][json-schema] (draft 2020-12) for a configuration.
+//!
+//! The schema describes the structure of configuration files and can be used
+//! by editors for autocompletion and validation of TOML, YAML and JSON5 files.
+//! See [`json_schema`].
+//!
+//! [json-schema]: https://json-schema.org
+
+use std::fmt::{self, Write};
+
+use crate::{
+    meta::{Constraint, Expr, Field, FieldKind, Float, LeafKind, MapKey, Meta, Type},
+    Config,
+};
+
+
+/// Generates a JSON Schema (draft 2020-12) for the configuration `C`.
+///
+/// The schema is built from [`Config::META`]:
+///
+/// - Nested configurations are objects with `additionalProperties: false`,
+///   i.e. unknown keys are rejected like in strict mode.
+/// - Values that are required and have no default value are listed in
+///   `required`. Note that the schema only describes a single file: required
+///   values that are provided by other sources (e.g. env variables) will still
+///   be reported as missing when validating the file alone.
+/// - Doc comments become `description`s and default values `default`s.
+/// - Types are derived from the field types (see [`meta::Type`][crate::meta::Type]).
+///   Fields with unknown types accept any value.
+/// - `min`, `max`, `one_of` and `non_empty` constraints are included.
+/// - Deprecated fields and aliases are marked with `deprecated: true`, secret
+///   fields with `writeOnly: true`.
+///
+/// ```
+/// use confique::Config;
+///
+/// /// App configuration.
+/// #[derive(Config)]
+/// struct Conf {
+///     /// The port to listen on.
+///     #[config(default = 8080)]
+///     port: u16,
+///     name: String,
+/// }
+///
+/// let schema = confique::schema::json_schema::<Conf>();
+/// assert!(schema.contains(r#""$schema": "https://json-schema.org/draft/2020-12/schema""#));
+/// assert!(schema.contains(r#""required": ["name"]"#));
+/// ```
+pub fn json_schema<C: Config>() -> String {
+    let mut root = Object::new();
+    root.set("$schema", Json::str("https://json-schema.org/draft/2020-12/schema"));
+    root.set("title", Json::str(C::META.name));
+    add_description(&mut root, C::META.doc);
+    object_schema(&mut root, &C::META);
+
+    let mut out = String::new();
+    Json::Object(root).write(&mut out, 0).expect("writing to a string cannot fail");
+    out.push('\n');
+    out
+}
+
+/// Adds the schema entries for an object described by `meta` to `obj`.
+fn object_schema(obj: &mut Object, meta: &Meta) {
+    let mut properties = Object::new();
+    let mut required = Vec::new();
+    let mut required_alternatives = Vec::new();
+
+    for field in meta.fields {
+        let schema = field_schema(field);
+        if is_required(field) {
+            if field.aliases.is_empty() {
+                required.push(Json::str(field.name));
+            } else {
+                // The value can also be specified via one of the aliases.
+                let alternatives = std::iter::once(field.name)
+                    .chain(field.aliases.iter().copied())
+                    .map(|name| {
+                        let mut o = Object::new();
+                        o.set("required", Json::Array(vec![Json::str(name)]));
+                        Json::Object(o)
+                    })
+                    .collect();
+                let mut o = Object::new();
+                o.set("anyOf", Json::Array(alternatives));
+                required_alternatives.push(Json::Object(o));
+            }
+        }
+
+        for alias in field.aliases {
+            let mut alias_schema = schema.clone();
+            alias_schema.set("deprecated", Json::Bool(true));
+            properties.set(alias, Json::Object(alias_schema));
+        }
+        properties.set(field.name, Json::Object(schema));
+    }
+
+    obj.set("type", Json::str("object"));
+    obj.set("properties", Json::Object(properties));
+    if !required.is_empty() {
+        obj.set("required", Json::Array(required));
+    }
+    if !required_alternatives.is_empty() {
+        obj.set("allOf", Json::Array(required_alternatives));
+    }
+    obj.set("additionalProperties", Json::Bool(false));
+}
+
+fn field_schema(field: &Field) -> Object {
+    let mut obj = Object::new();
+
+    match field.kind {
+        FieldKind::Nested { meta, .. } => {
+            let doc = if field.doc.is_empty() { meta.doc } else { field.doc };
+            add_description(&mut obj, doc);
+            object_schema(&mut obj, meta);
+        }
+        FieldKind::Leaf { constraints, ty, kind, .. } => {
+            add_description(&mut obj, field.doc);
+            type_schema(&mut obj, &ty);
+            for constraint in constraints {
+                constraint_schema(&mut obj, constraint, &ty);
+            }
+            if let LeafKind::Required { default: Some(default) } = kind {
+                if let Some(default) = expr_to_json(&default) {
+                    obj.set("default", default);
+                }
+            }
+        }
+    }
+
+    if field.deprecated.is_some() {
+        obj.set("deprecated", Json::Bool(true));
+    }
+    if field.secret {
+        obj.set("writeOnly", Json::Bool(true));
+    }
+
+    obj
+}
+
+/// Returns whether a value for `field` has to be present. For nested fields,
+/// that's the case if any of its fields is required.
+fn is_required(field: &Field) -> bool {
+    match field.kind {
+        FieldKind::Leaf { kind: LeafKind::Required { default }, .. } => default.is_none(),
+        FieldKind::Leaf { kind: LeafKind::Optional, .. } => false,
+        FieldKind::Nested { meta, .. } => meta.fields.iter().any(is_required),
+    }
+}
+
+fn type_schema(obj: &mut Object, ty: &Type) {
+    match ty {
+        Type::Bool => obj.set("type", Json::str("boolean")),
+        Type::Integer { min, max } => {
+            obj.set("type", Json::str("integer"));
+            obj.set("minimum", Json::Number(min.to_string()));
+            obj.set("maximum", Json::Number(max.to_string()));
+        }
+        Type::Float => obj.set("type", Json::str("number")),
+        Type::String => obj.set("type", Json::str("string")),
+        Type::Array(item) => {
+            let mut items = Object::new();
+            type_schema(&mut items, item);
+            obj.set("type", Json::str("array"));
+            obj.set("items", Json::Object(items));
+        }
+        Type::Map(value) => {
+            let mut values = Object::new();
+            type_schema(&mut values, value);
+            obj.set("type", Json::str("object"));
+            obj.set("additionalProperties", Json::Object(values));
+        }
+        Type::Any => {}
+    }
+}
+
+fn constraint_schema(obj: &mut Object, constraint: &Constraint, ty: &Type) {
+    match constraint {
+        // JSON Schema only supports numeric limits.
+        Constraint::Min(e @ (Expr::Integer(_) | Expr::Float(_))) => {
+            if let Some(v) = expr_to_json(e) {
+                obj.set("minimum", v);
+            }
+        }
+        Constraint::Max(e @ (Expr::Integer(_) | Expr::Float(_))) => {
+            if let Some(v) = expr_to_json(e) {
+                obj.set("maximum", v);
+            }
+        }
+        Constraint::Min(_) | Constraint::Max(_) => {}
+        Constraint::OneOf(values) => {
+            obj.set("enum", Json::Array(values.iter().filter_map(expr_to_json).collect()));
+        }
+        Constraint::NonEmpty => {
+            let key = match ty {
+                Type::String => "minLength",
+                Type::Array(_) => "minItems",
+                Type::Map(_) => "minProperties",
+                _ => return,
+            };
+            obj.set(key, Json::Number("1".into()));
+        }
+    }
+}
+
+/// Converts an expression to JSON. Returns `None` for floats that cannot be
+/// represented in JSON (NaN and infinities).
+fn expr_to_json(expr: &Expr) -> Option<Json> {
+    let out = match expr {
+        Expr::Str(s) => Json::str(s),
+        Expr::Float(f) => {
+            let finite = match f {
+                Float::F32(f) => f.is_finite(),
+                Float::F64(f) => f.is_finite(),
+            };
+            if !finite {
+                return None;
+            }
+            Json::Number(f.to_string())
+        }
+        Expr::Integer(i) => Json::Number(i.to_string()),
+        Expr::Bool(b) => Json::Bool(*b),
+        Expr::Array(items) => Json::Array(
+            items.iter().map(expr_to_json).collect::<Option<_>>()?,
+        ),
+        Expr::Map(entries) => {
+            let mut obj = Object::new();
+            for entry in *entries {
+                // JSON only has string keys.
+                let key = match entry.key {
+                    MapKey::Str(s) => s.to_owned(),
+                    MapKey::Float(f) => f.to_string(),
+                    MapKey::Integer(i) => i.to_string(),
+                    MapKey::Bool(b) => b.to_string(),
+                };
+                obj.set(&key, expr_to_json(&entry.value)?);
+            }
+            Json::Object(obj)
+        }
+    };
+
+    Some(out)
+}
+
+/// Sets `description` to the doc comment lines, with the one leading space
+/// typical for doc comments removed.
+fn add_description(obj: &mut Object, doc: &[&str]) {
+    let lines = doc.iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>();
+    let description = lines.join("\n");
+    let description = description.trim();
+    if !description.is_empty() {
+        obj.set("description", Json::str(description));
+    }
+}
+
+
+/// Minimal JSON representation used to build the schema.
+#[derive(Debug, Clone)]
+enum Json {
+    Bool(bool),
+    /// Already formatted number.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Object),
+}
+
+/// A JSON object, keeping the insertion order of its keys.
+#[derive(Debug, Clone)]
+struct Object(Vec<(String, Json)>);
+
+impl Object {
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Sets `key` to `value`, replacing the value if the key already exists.
+    fn set(&mut self, key: &str, value: Json) {
+        match self.0.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.0.push((key.to_owned(), value)),
+        }
+    }
+}
+
+impl Json {
+    fn str(s: &str) -> Self {
+        Self::String(s.to_owned())
+    }
+
+    /// Whether this value is printed on a single line.
+    fn is_simple(&self) -> bool {
+        match self {
+            Self::Array(items) => items.iter().all(|item| {
+                !matches!(item, Self::Array(_) | Self::Object(_))
+            }),
+            Self::Object(obj) => obj.0.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// Writes this value pretty-printed, with nested lines indented by
+    /// `indent + 1` levels.
+    fn write(&self, out: &mut String, indent: usize) -> fmt::Result {
+        match self {
+            Self::Bool(b) => write!(out, "{b}"),
+            Self::Number(n) => out.write_str(n),
+            Self::String(s) => write_string(out, s),
+            Self::Array(items) if self.is_simple() => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    item.write(out, indent)?;
+                }
+                out.push(']');
+                Ok(())
+            }
+            Self::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(if i > 0 { ",\n" } else { "\n" });
+                    push_indent(out, indent + 1);
+                    item.write(out, indent + 1)?;
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push(']');
+                Ok(())
+            }
+            Self::Object(obj) if obj.0.is_empty() => {
+                out.push_str("{}");
+                Ok(())
+            }
+            Self::Object(obj) => {
+                out.push('{');
+                for (i, (key, value)) in obj.0.iter().enumerate() {
+                    out.push_str(if i > 0 { ",\n" } else { "\n" });
+                    push_indent(out, indent + 1);
+                    write_string(out, key)?;
+                    out.push_str(": ");
+                    value.write(out, indent + 1)?;
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push('}');
+                Ok(())
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_string(out: &mut String, s: &str) -> fmt::Result {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    Ok(())
+}
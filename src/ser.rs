@@ -0,0 +1,337 @@
+//! Serializing configuration values into a simple intermediate representation,
+//! used by the `to_string` functions of the format modules.
+
+use std::fmt;
+
+use serde::ser::{self, Serialize};
+
+use crate::{error::ErrorInner, Error};
+
+
+/// A serialized value. Structs and maps are both represented as `Map`, enums
+/// are externally tagged (like in `serde_json`).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    /// `None` or the unit value.
+    None,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Str(String),
+    Seq(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+}
+
+impl Value {
+    /// Serializes `value` into a `Value`.
+    pub(crate) fn from_serialize<T: Serialize + ?Sized>(value: &T) -> Result<Self, Error> {
+        value.serialize(ValueSerializer).map_err(|SerError(msg)| {
+            ErrorInner::Serialization { path: None, msg }.into()
+        })
+    }
+
+    /// Returns the value of the entry with the string key `key`, if this is a
+    /// map containing such an entry.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Map(entries) => entries.iter()
+                .find(|(k, _)| matches!(k, Self::Str(s) if s == key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            Self::None => serializer.serialize_none(),
+            Self::Bool(v) => serializer.serialize_bool(*v),
+            Self::I64(v) => serializer.serialize_i64(*v),
+            Self::U64(v) => serializer.serialize_u64(*v),
+            Self::F32(v) => serializer.serialize_f32(*v),
+            Self::F64(v) => serializer.serialize_f64(*v),
+            Self::Str(v) => serializer.serialize_str(v),
+            Self::Seq(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Self::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+
+#[derive(Debug)]
+struct SerError(String);
+
+impl fmt::Display for SerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SerError {}
+
+impl ser::Error for SerError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = VariantSerializer<MapSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerError> { Ok(Value::Bool(v)) }
+    fn serialize_i8(self, v: i8) -> Result<Value, SerError> { Ok(Value::I64(v.into())) }
+    fn serialize_i16(self, v: i16) -> Result<Value, SerError> { Ok(Value::I64(v.into())) }
+    fn serialize_i32(self, v: i32) -> Result<Value, SerError> { Ok(Value::I64(v.into())) }
+    fn serialize_i64(self, v: i64) -> Result<Value, SerError> { Ok(Value::I64(v)) }
+    fn serialize_u8(self, v: u8) -> Result<Value, SerError> { Ok(Value::U64(v.into())) }
+    fn serialize_u16(self, v: u16) -> Result<Value, SerError> { Ok(Value::U64(v.into())) }
+    fn serialize_u32(self, v: u32) -> Result<Value, SerError> { Ok(Value::U64(v.into())) }
+    fn serialize_u64(self, v: u64) -> Result<Value, SerError> { Ok(Value::U64(v)) }
+    fn serialize_f32(self, v: f32) -> Result<Value, SerError> { Ok(Value::F32(v)) }
+    fn serialize_f64(self, v: f64) -> Result<Value, SerError> { Ok(Value::F64(v)) }
+    fn serialize_char(self, v: char) -> Result<Value, SerError> { Ok(Value::Str(v.into())) }
+    fn serialize_str(self, v: &str) -> Result<Value, SerError> { Ok(Value::Str(v.into())) }
+    fn serialize_none(self) -> Result<Value, SerError> { Ok(Value::None) }
+    fn serialize_unit(self) -> Result<Value, SerError> { Ok(Value::None) }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerError> {
+        Ok(Value::Seq(v.iter().map(|b| Value::U64((*b).into())).collect()))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, SerError> {
+        Ok(Value::None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerError> {
+        Ok(Value::Str(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, SerError> {
+        Ok(Value::Map(vec![(Value::Str(variant.into()), value.serialize(self)?)]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, SerError> {
+        Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, SerError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, SerError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerError> {
+        Ok(VariantSerializer { variant, inner: self.serialize_seq(Some(len))? })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, SerError> {
+        Ok(MapSerializer { entries: Vec::with_capacity(len.unwrap_or(0)), key: None })
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<MapSerializer, SerError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerError> {
+        Ok(VariantSerializer { variant, inner: self.serialize_map(Some(len))? })
+    }
+}
+
+struct SeqSerializer(Vec<Value>);
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.0.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = SerError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerError> {
+        Ok(Value::Seq(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = SerError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerError> {
+        Ok(Value::Seq(self.0))
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = SerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerError> {
+        Ok(Value::Seq(self.0))
+    }
+}
+
+struct MapSerializer {
+    entries: Vec<(Value, Value)>,
+
+    /// The key passed to `serialize_key`, waiting for its value.
+    key: Option<Value>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = SerError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerError> {
+        self.key = Some(key.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        let key = self.key.take().expect("`serialize_value` called before `serialize_key`");
+        self.entries.push((key, value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerError> {
+        Ok(Value::Map(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = SerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        self.entries.push((Value::Str(key.into()), value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerError> {
+        Ok(Value::Map(self.entries))
+    }
+}
+
+/// Wraps the value of a tuple or struct variant in a map with the variant
+/// name as only key.
+struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = Value;
+    type Error = SerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerError> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Value, SerError> {
+        Ok(Value::Map(vec![(Value::Str(self.variant.into()), Value::Seq(self.inner.0))]))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<MapSerializer> {
+    type Ok = Value;
+    type Error = SerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Value, SerError> {
+        let inner = Value::Map(self.inner.entries);
+        Ok(Value::Map(vec![(Value::Str(self.variant.into()), inner)]))
+    }
+}
@@ -0,0 +1,195 @@
+//! Detecting unknown keys in configuration files (strict mode).
+
+use std::{fmt, path::Path};
+
+use serde::de::{self, Deserialize, IgnoredAny};
+
+use crate::{
+    dotted,
+    error::ErrorInner,
+    meta::{FieldKind, Meta},
+    Error, ValueSource, Warning,
+};
+
+
+/// The keys of a deserialized document. Only maps have keys; for all other
+/// values, this is empty.
+#[derive(Debug, Default)]
+pub(crate) struct Keys(Vec<(String, Keys)>);
+
+/// A key that does not correspond to any field, see [`unknown_keys`].
+pub(crate) struct UnknownKey {
+    /// The dotted path of the key.
+    pub(crate) path: String,
+
+    /// The dotted path of a similarly named field.
+    pub(crate) suggestion: Option<String>,
+}
+
+impl UnknownKey {
+    pub(crate) fn into_error(self, file: &Path) -> Error {
+        ErrorInner::UnknownKey {
+            path: self.path,
+            suggestion: self.suggestion,
+            source: ValueSource::File(file.to_owned()),
+        }.into()
+    }
+
+    pub(crate) fn into_warning(self, file: &Path) -> Warning {
+        Warning::UnknownKey {
+            path: self.path,
+            suggestion: self.suggestion,
+            source: ValueSource::File(file.to_owned()),
+        }
+    }
+}
+
+/// A key that is an alias of a field (`#[config(alias = "...")]`).
+pub(crate) struct AliasKey {
+    /// The dotted path of the key as used, e.g. `log.old_name`.
+    pub(crate) alias: String,
+
+    /// The dotted path of the field, e.g. `log.new_name`.
+    pub(crate) path: String,
+}
+
+impl AliasKey {
+    pub(crate) fn into_warning(self, file: &Path) -> Warning {
+        Warning::DeprecatedAlias {
+            replacement: self.path.clone(),
+            alias: self.alias,
+            path: self.path,
+            source: ValueSource::File(file.to_owned()),
+        }
+    }
+}
+
+/// Checks all keys in `keys` against the fields in `meta`, at every nesting
+/// level. Returns all keys that do not correspond to any field and all keys
+/// that are aliases of fields. Values of leaf fields are not checked, as those
+/// might be maps themselves.
+pub(crate) fn check_keys(meta: &Meta, keys: &Keys) -> (Vec<UnknownKey>, Vec<AliasKey>) {
+    /// `prefix` is the path of `keys` as written in the file, `canonical` the
+    /// path without any aliases.
+    fn imp(
+        meta: &Meta,
+        keys: &Keys,
+        (prefix, canonical): (&str, &str),
+        unknown: &mut Vec<UnknownKey>,
+        aliases: &mut Vec<AliasKey>,
+    ) {
+        let join = |prefix: &str, name: &str| match prefix.is_empty() {
+            true => name.to_owned(),
+            false => format!("{prefix}.{name}"),
+        };
+
+        for (key, value) in &keys.0 {
+            let field = meta.fields.iter()
+                .find(|f| f.name == key || f.aliases.contains(&&**key));
+            match field {
+                Some(field) => {
+                    let path = join(canonical, field.name);
+                    if field.name != key {
+                        aliases.push(AliasKey { alias: join(prefix, key), path: path.clone() });
+                    }
+                    if let FieldKind::Nested { meta, .. } = field.kind {
+                        imp(meta, value, (&join(prefix, key), &path), unknown, aliases);
+                    }
+                }
+                None => {
+                    let suggestion = dotted::suggestion(meta.fields.iter().map(|f| f.name), key);
+                    unknown.push(UnknownKey {
+                        path: join(prefix, key),
+                        suggestion: suggestion.map(|s| join(canonical, s)),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut unknown = Vec::new();
+    let mut aliases = Vec::new();
+    imp(meta, keys, ("", ""), &mut unknown, &mut aliases);
+    (unknown, aliases)
+}
+
+impl<'de> Deserialize<'de> for Keys {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KeysVisitor)
+    }
+}
+
+struct KeysVisitor;
+
+impl<'de> de::Visitor<'de> for KeysVisitor {
+    type Value = Keys;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Keys, A::Error> {
+        let mut out = Vec::new();
+        while let Some(key) = map.next_key::<Key>()? {
+            out.push((key.0, map.next_value()?));
+        }
+        Ok(Keys(out))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Keys, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(Keys::default())
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Keys, D::Error> {
+        Keys::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Keys, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Keys::deserialize(deserializer)
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Keys, A::Error> {
+        IgnoredAny.visit_enum(data).map(|_| Keys::default())
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_i64<E>(self, _: i64) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_i128<E>(self, _: i128) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_u64<E>(self, _: u64) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_u128<E>(self, _: u128) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_f64<E>(self, _: f64) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_str<E>(self, _: &str) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_bytes<E>(self, _: &[u8]) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_none<E>(self) -> Result<Keys, E> { Ok(Keys::default()) }
+    fn visit_unit<E>(self) -> Result<Keys, E> { Ok(Keys::default()) }
+}
+
+/// A map key, converted to a string. Non-string keys (e.g. integers in YAML)
+/// never match a field name, but are still reported.
+struct Key(String);
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> de::Visitor<'de> for KeyVisitor {
+            type Value = Key;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map key")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Key, E> { Ok(Key(v.to_owned())) }
+            fn visit_bool<E>(self, v: bool) -> Result<Key, E> { Ok(Key(v.to_string())) }
+            fn visit_i64<E>(self, v: i64) -> Result<Key, E> { Ok(Key(v.to_string())) }
+            fn visit_u64<E>(self, v: u64) -> Result<Key, E> { Ok(Key(v.to_string())) }
+            fn visit_f64<E>(self, v: f64) -> Result<Key, E> { Ok(Key(v.to_string())) }
+        }
+
+        deserializer.deserialize_any(KeyVisitor)
+    }
+}
@@ -6,7 +6,13 @@
 
 use std::fmt;
 
-use crate::meta::{Meta, FieldKind, LeafKind, Expr};
+use crate::{
+    error::ErrorInner,
+    internal::EnvScope,
+    meta::{Meta, FieldKind, LeafKind, Expr, Constraint},
+    ser::Value,
+    Error, Provenance, ValueSource,
+};
 
 
 /// Trait abstracting over the format differences when it comes to formatting a
@@ -30,6 +36,15 @@ pub(crate) trait Formatter {
     /// Write a commented-out field with optional value, e.g. `format!("#{name} = {value}")`.
     fn disabled_field(&mut self, name: &'static str, value: Option<&'static Expr>);
 
+    /// Write a field with the given (non-`None`) value, e.g.
+    /// `format!("{name} = {value}")`. Returns an error message if the value
+    /// cannot be represented in this format.
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), String>;
+
+    /// Write a commented-out field whose value is secret and thus not shown,
+    /// e.g. `format!("#{name} = <redacted>")`.
+    fn redacted_field(&mut self, name: &'static str);
+
     /// Start a nested configuration section with the given name.
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]);
 
@@ -46,10 +61,54 @@ pub(crate) trait Formatter {
 
     /// Emits a comment describing that this field can be loaded from the given
     /// env var. Default impl is likely sufficient.
-    fn env_comment(&mut self, env_key: &'static str) {
+    fn env_comment(&mut self, env_key: &str) {
         self.comment(format_args!(" Can also be specified via environment variable `{env_key}`."));
     }
 
+    /// Emits a comment describing that this field can be loaded from the file
+    /// whose path is specified in the env var `<env_key>_FILE`. Default impl
+    /// is likely sufficient.
+    fn env_file_suffix_comment(&mut self, env_key: &str) {
+        self.comment(format_args!(
+            " Or via a file whose path is specified in environment variable `{env_key}_FILE`."
+        ));
+    }
+
+    /// Emits a comment describing that this field can also be loaded from the
+    /// given deprecated env vars (`#[config(alias_env = "...")]`). Default
+    /// impl is likely sufficient.
+    fn env_aliases_comment(&mut self, env_keys: &[String]) {
+        let keys = env_keys.iter().map(|k| format!("`{k}`")).collect::<Vec<_>>().join(", ");
+        self.comment(format_args!(" Deprecated environment variables: {keys}"));
+    }
+
+    /// Emits a comment stating that this field is deprecated, with the message
+    /// of the `#[config(deprecated = "...")]` attribute. Default impl is likely
+    /// sufficient.
+    fn deprecated_comment(&mut self, message: &str) {
+        self.comment(format_args!(" Deprecated: {message}"));
+    }
+
+    /// Emits a comment listing the old names of this field that are still
+    /// accepted (`#[config(alias = "...")]`). Default impl is likely
+    /// sufficient.
+    fn aliases_comment(&mut self, aliases: &[&str]) {
+        let aliases = aliases.iter().map(|a| format!("`{a}`")).collect::<Vec<_>>().join(", ");
+        self.comment(format_args!(" Deprecated names: {aliases}"));
+    }
+
+    /// Emits a comment stating the source that supplied the value of this
+    /// field. Default impl is likely sufficient.
+    fn provenance_comment(&mut self, source: &ValueSource) {
+        self.comment(format_args!(" Source: {source}"));
+    }
+
+    /// Emits a comment describing that this field can be loaded from the given
+    /// systemd credential. Default impl is likely sufficient.
+    fn credential_comment(&mut self, name: &str) {
+        self.comment(format_args!(" Can also be specified via systemd credential `{name}`."));
+    }
+
     /// Emits a comment either stating that this field is required, or
     /// specifying the default value. Default impl is likely sufficient.
     fn default_or_required_comment(&mut self, default_value: Option<&'static Expr>) {
@@ -59,6 +118,45 @@ pub(crate) trait Formatter {
         }
     }
 
+    /// Emits comments describing the constraints of this field (e.g. the
+    /// allowed range). Only called if there are any. Default impl is likely
+    /// sufficient.
+    fn constraints_comment(&mut self, constraints: &'static [Constraint]) {
+        let find = |f: fn(&'static Constraint) -> Option<&'static Expr>| {
+            constraints.iter().find_map(f)
+        };
+        let min = find(|c| match c { Constraint::Min(v) => Some(v), _ => None });
+        let max = find(|c| match c { Constraint::Max(v) => Some(v), _ => None });
+        match (min, max) {
+            (Some(min), Some(max)) => self.comment(format_args!(
+                " Range: {}..={}",
+                Self::ExprPrinter::from(min),
+                Self::ExprPrinter::from(max),
+            )),
+            (Some(min), None) => {
+                self.comment(format_args!(" Minimum: {}", Self::ExprPrinter::from(min)));
+            }
+            (None, Some(max)) => {
+                self.comment(format_args!(" Maximum: {}", Self::ExprPrinter::from(max)));
+            }
+            (None, None) => {}
+        }
+
+        for constraint in constraints {
+            match constraint {
+                Constraint::OneOf(values) => {
+                    let values = values.iter()
+                        .map(|v| Self::ExprPrinter::from(v).to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.comment(format_args!(" Allowed values: {values}"));
+                }
+                Constraint::NonEmpty => self.comment(" Must not be empty."),
+                _ => {}
+            }
+        }
+    }
+
     /// Makes sure that there is a gap of at least `size` many empty lines at
     /// the end of the buffer. Does nothing when the buffer is empty.
     fn make_gap(&mut self, size: u8) {
@@ -147,6 +245,30 @@ impl Default for FormatOptions {
 /// functions in the format-specific modules (e.g. `toml::format`,
 /// `yaml::format`).
 pub(crate) fn format(meta: &Meta, out: &mut impl Formatter, options: FormatOptions) {
+    format_root(meta, None, out, options).expect("bug: formatting template failed");
+}
+
+/// Formats the given configuration values (the serialized configuration) with
+/// the given formatter, with the same comments as the template. The values of
+/// secret fields are not included. If `provenance` is given, the source of
+/// each value is stated in a comment above it, regardless of
+/// `options.comments`.
+pub(crate) fn format_values(
+    meta: &Meta,
+    values: &Value,
+    provenance: Option<&Provenance>,
+    out: &mut impl Formatter,
+    options: FormatOptions,
+) -> Result<(), Error> {
+    format_root(meta, Some((values, provenance)), out, options)
+}
+
+fn format_root(
+    meta: &Meta,
+    values: Option<(&Value, Option<&Provenance>)>,
+    out: &mut impl Formatter,
+    options: FormatOptions,
+) -> Result<(), Error> {
     // Print root docs.
     if options.comments {
         meta.doc.iter().for_each(|doc| out.comment(doc));
@@ -154,25 +276,40 @@ pub(crate) fn format(meta: &Meta, out: &mut impl Formatter, options: FormatOptio
 
     // Recursively format all nested objects and fields
     out.start_main();
-    format_impl(out, meta, &options);
+    let (values, provenance) = values.unzip();
+    let scope = EnvScope::root(meta.env_prefix);
+    format_impl(out, meta, values, provenance.flatten(), &scope, &options)?;
     out.end_main();
     out.assert_single_trailing_newline();
+    Ok(())
 }
 
 
-fn format_impl(out: &mut impl Formatter, meta: &Meta, options: &FormatOptions) {
+/// Formats all fields of `meta`. If `values` is `None`, a template is emitted
+/// with defaults as values, otherwise the values are taken from `values`.
+fn format_impl(
+    out: &mut impl Formatter,
+    meta: &Meta,
+    values: Option<&Value>,
+    provenance: Option<&Provenance>,
+    scope: &EnvScope,
+    options: &FormatOptions,
+) -> Result<(), Error> {
     // Output all leaf fields first
-    let leaf_fields = meta.fields.iter().filter_map(|f| match &f.kind {
-        FieldKind::Leaf { kind, env } => Some((f, kind, env)),
-        _ => None,
-    });
+    let leaf_fields = meta.fields.iter().filter(|f| matches!(f.kind, FieldKind::Leaf { .. }));
     let mut emitted_anything = false;
-    for (i, (field, kind, env)) in leaf_fields.enumerate() {
+    for (i, field) in leaf_fields.enumerate() {
+        let FieldKind::Leaf {
+            kind, env, env_aliases, env_file_suffix, credential, constraints, ..
+        } = &field.kind else {
+            unreachable!("`leaf_fields` only contains leaves")
+        };
         emitted_anything = true;
 
         if i > 0 {
             out.make_gap(options.leaf_field_gap());
         }
+        let field_start = out.buffer().len();
 
         let mut emitted_something = false;
         macro_rules! empty_sep_doc_line {
@@ -187,33 +324,88 @@ fn format_impl(out: &mut impl Formatter, meta: &Meta, options: &FormatOptions) {
             field.doc.iter().for_each(|doc| out.comment(doc));
             emitted_something = !field.doc.is_empty();
 
-            if let Some(env) = env {
+            if field.deprecated.is_some() || !field.aliases.is_empty() {
                 empty_sep_doc_line!();
-                out.env_comment(env);
+                if let Some(message) = field.deprecated {
+                    out.deprecated_comment(message);
+                }
+                if !field.aliases.is_empty() {
+                    out.aliases_comment(field.aliases);
+                }
+                emitted_something = true;
             }
-        }
 
-        match kind {
-            LeafKind::Optional => out.disabled_field(field.name, None),
-            LeafKind::Required { default } => {
-                // Emit comment about default value or the value being required.
-                if options.comments {
+            let env_key = scope.key(*env, field.name).filter(|_| options.env_keys);
+            if let Some(key) = &env_key {
+                empty_sep_doc_line!();
+                out.env_comment(key);
+                if *env_file_suffix {
+                    out.env_file_suffix_comment(key);
+                }
+                if !env_aliases.is_empty() {
+                    let keys = env_aliases.iter()
+                        .map(|alias| scope.key(Some(alias), field.name).unwrap())
+                        .collect::<Vec<_>>();
+                    out.env_aliases_comment(&keys);
+                }
+            }
+
+            // Credential and env comments are grouped together.
+            if let Some(name) = credential {
+                if env_key.is_none() {
                     empty_sep_doc_line!();
-                    out.default_or_required_comment(default.as_ref())
                 }
+                out.credential_comment(name);
+            }
+        }
+
+        let default = match kind {
+            LeafKind::Optional => None,
+            LeafKind::Required { default } => default.as_ref(),
+        };
+
+        // Emit comment about default value or the value being required,
+        // followed by the constraints. When formatting values, "required" is
+        // not mentioned as the value is obviously set.
+        if options.comments {
+            let required_comment = matches!(kind, LeafKind::Required { .. })
+                && (values.is_none() || default.is_some());
+            if required_comment || !constraints.is_empty() {
+                empty_sep_doc_line!();
+            }
+            if required_comment {
+                out.default_or_required_comment(default);
+            }
+            if !constraints.is_empty() {
+                out.constraints_comment(constraints);
+            }
+        }
 
-                // Emit the actual line with the name and optional value
-                out.disabled_field(field.name, default.as_ref());
+        // The source of the value is emitted last, right above the value.
+        if let Some(source) = provenance.and_then(|p| p.get(&scope.path(field.name))) {
+            if out.buffer().len() > field_start {
+                out.comment("");
             }
+            out.provenance_comment(source);
+        }
+
+        // Emit the actual line with the name and value
+        match values.map(|v| v.get(field.name).unwrap_or(&Value::None)) {
+            None => out.disabled_field(field.name, default),
+            Some(Value::None) => out.disabled_field(field.name, None),
+            Some(_) if field.secret => out.redacted_field(field.name),
+            Some(value) => out.field(field.name, value).map_err(|msg| -> Error {
+                ErrorInner::Serialization { path: Some(scope.path(field.name)), msg }.into()
+            })?,
         }
     }
 
     // Then all nested fields recursively
     let nested_fields = meta.fields.iter().filter_map(|f| match &f.kind {
-        FieldKind::Nested { meta } => Some((f, meta)),
+        FieldKind::Nested { meta, env_prefix } => Some((f, meta, env_prefix)),
         _ => None,
     });
-    for (field, meta) in nested_fields {
+    for (field, meta, env_prefix) in nested_fields {
         if emitted_anything {
             out.make_gap(options.nested_field_gap);
         }
@@ -221,7 +413,11 @@ fn format_impl(out: &mut impl Formatter, meta: &Meta, options: &FormatOptions) {
 
         let comments = if options.comments { field.doc } else { &[] };
         out.start_nested(field.name, comments);
-        format_impl(out, meta, options);
+        let scope = scope.nested(field.name, *env_prefix, meta.env_prefix);
+        let values = values.map(|v| v.get(field.name).unwrap_or(&Value::None));
+        format_impl(out, meta, values, provenance, &scope, options)?;
         out.end_nested();
     }
+
+    Ok(())
 }
@@ -1,4 +1,7 @@
+// The example configurations are only used for their `META`.
+#[allow(dead_code)]
 pub(crate) mod example1;
+#[allow(dead_code)]
 pub(crate) mod example2;
 
 
@@ -3,15 +3,19 @@
 
 use std::fmt::{self, Write};
 
+use serde::Serialize;
+
 use crate::{
     meta::{Expr, MapKey},
+    ser::Value,
     template::{self, Formatter},
-    Config,
+    Config, Error, Provenance,
 };
 
 
 
 /// Options for generating a TOML template.
+#[derive(Default)]
 #[non_exhaustive]
 pub struct FormatOptions {
     /// Indentation for nested tables. Default: 0.
@@ -21,15 +25,6 @@ pub struct FormatOptions {
     pub general: template::FormatOptions,
 }
 
-impl Default for FormatOptions {
-    fn default() -> Self {
-        Self {
-            indent: 0,
-            general: Default::default(),
-        }
-    }
-}
-
 /// Formats the configuration description as a TOML file.
 ///
 /// This can be used to generate a template file that you can give to the users
@@ -97,6 +92,135 @@ pub fn template<C: Config>(options: FormatOptions) -> String {
     out.finish()
 }
 
+/// Formats the given configuration as TOML, e.g. to show the effective
+/// configuration of your application.
+///
+/// The output is structured like the [template][template()] and includes the
+/// same comments (depending on `options`), but contains the given values
+/// instead of the defaults. Optional values that are not set are commented out.
+/// The values of secret fields (`#[config(secret)]`) are never included.
+///
+/// The configuration type has to implement `Serialize`, usually via the
+/// `#[config(serialize)]` attribute, which makes sure that the serialized
+/// names match the names in [`Config::META`]. An error is returned if
+/// serialization fails or if a value cannot be represented in TOML (e.g. a
+/// `None` inside a list).
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use confique::{Config, toml::FormatOptions};
+///
+/// #[derive(Config)]
+/// #[config(serialize)]
+/// struct Conf {
+///     /// The port to listen on.
+///     #[config(default = 8080)]
+///     port: u16,
+///
+///     #[config(secret)]
+///     password: String,
+///
+///     #[config(nested)]
+///     log: LogConfig,
+/// }
+///
+/// #[derive(Config)]
+/// #[config(serialize)]
+/// struct LogConfig {
+///     stdout: bool,
+///     file: Option<String>,
+/// }
+///
+/// const EXPECTED: &str = "\
+/// ## The port to listen on.
+/// ##
+/// ## Default value: 8080
+/// port = 80
+///
+/// ##password = <redacted>
+///
+/// [log]
+/// stdout = true
+///
+/// ##file =
+/// ";
+///
+/// fn main() {
+///     let conf = Conf {
+///         port: 80,
+///         password: "hunter2".into(),
+///         log: LogConfig { stdout: true, file: None },
+///     };
+///     let toml = confique::toml::to_string(&conf, FormatOptions::default()).unwrap();
+///     assert_eq!(toml, EXPECTED);
+/// }
+/// ```
+pub fn to_string<C: Config + Serialize>(
+    config: &C,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    format_values(config, None, options)
+}
+
+/// Like [`to_string`], but additionally states the source of each value in a
+/// comment above it, as recorded in `provenance`. This comment is included
+/// even if `options.general.comments` is `false`.
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use confique::{Config, toml::FormatOptions};
+///
+/// #[derive(Config)]
+/// #[config(serialize)]
+/// #[config(env_prefix = "APP_")]
+/// struct Conf {
+///     #[config(default = 8080)]
+///     port: u16,
+///
+///     #[config(default = "localhost")]
+///     host: String,
+/// }
+///
+/// fn main() -> Result<(), confique::Error> {
+///     let (conf, provenance) = Conf::builder()
+///         .env_from([("APP_PORT".to_owned(), "80".to_owned())])
+///         .load_with_provenance()?;
+///
+///     let mut options = FormatOptions::default();
+///     options.general.comments = false;
+///     let toml = confique::toml::to_string_with_provenance(&conf, &provenance, options)?;
+///     assert_eq!(toml, "\
+///         ## Source: environment variable `APP_PORT`\n\
+///         port = 80\n\
+///         ## Source: default value\n\
+///         host = \"localhost\"\n\
+///     ");
+///     Ok(())
+/// }
+/// ```
+pub fn to_string_with_provenance<C: Config + Serialize>(
+    config: &C,
+    provenance: &Provenance,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    format_values(config, Some(provenance), options)
+}
+
+fn format_values<C: Config + Serialize>(
+    config: &C,
+    provenance: Option<&Provenance>,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    let values = Value::from_serialize(config)?;
+    let mut out = TomlFormatter::new(&options);
+    template::format_values(&C::META, &values, provenance, &mut out, options.general)?;
+    Ok(out.finish())
+}
+
 struct TomlFormatter {
     indent: u8,
     buffer: String,
@@ -131,17 +255,41 @@ impl Formatter for TomlFormatter {
     }
 
     fn disabled_field(&mut self, name: &str, value: Option<&'static Expr>) {
+        let name = PrintKey(name);
         match value.map(PrintExpr) {
             None => self.comment(format_args!("{name} =")),
             Some(v) => self.comment(format_args!("{name} = {v}")),
         };
     }
 
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), String> {
+        let value = match value {
+            // See `PrintExpr` for why floats are special cased.
+            Value::F32(v) if !v.is_nan() => v.to_string(),
+            Value::F64(v) if !v.is_nan() => v.to_string(),
+            _ => {
+                let mut s = String::new();
+                value.serialize(toml::ser::ValueSerializer::new(&mut s))
+                    .map_err(|e| e.to_string())?;
+                s
+            }
+        };
+
+        self.emit_indentation();
+        writeln!(self.buffer, "{} = {value}", PrintKey(name)).unwrap();
+        Ok(())
+    }
+
+    fn redacted_field(&mut self, name: &'static str) {
+        self.comment(format_args!("{} = <redacted>", PrintKey(name)));
+    }
+
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]) {
         self.stack.push(name);
         doc.iter().for_each(|doc| self.comment(doc));
         self.emit_indentation();
-        writeln!(self.buffer, "[{}]", self.stack.join(".")).unwrap();
+        let path = self.stack.iter().map(|k| PrintKey(k).to_string()).collect::<Vec<_>>();
+        writeln!(self.buffer, "[{}]", path.join(".")).unwrap();
     }
 
     fn end_nested(&mut self) {
@@ -179,7 +327,7 @@ impl fmt::Display for PrintExpr<'_> {
                     }
 
                     match entry.key {
-                        MapKey::Str(s) if is_valid_bare_key(s) => f.write_str(s)?,
+                        MapKey::Str(s) => PrintKey(s).fmt(f)?,
                         _ => PrintExpr(&entry.key.into()).fmt(f)?,
                     }
                     f.write_str(" = ")?;
@@ -208,8 +356,22 @@ impl fmt::Display for PrintExpr<'_> {
     }
 }
 
-fn is_valid_bare_key(s: &str) -> bool {
-    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+/// Helper to emit a key into TOML, quoted if it is not a valid bare key.
+struct PrintKey<'a>(&'a str);
+
+impl fmt::Display for PrintKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let is_bare = !self.0.is_empty()
+            && self.0.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if is_bare {
+            return f.write_str(self.0);
+        }
+
+        let mut s = String::new();
+        serde::Serialize::serialize(self.0, toml::ser::ValueSerializer::new(&mut s))
+            .expect("string serialization to TOML failed");
+        s.fmt(f)
+    }
 }
 
 #[cfg(test)]
@@ -234,6 +396,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn indent_2() {
         let mut options = FormatOptions::default();
         options.indent = 2;
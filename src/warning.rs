@@ -0,0 +1,138 @@
+//! Non-fatal problems noticed while loading a configuration.
+
+use std::{fmt, path::PathBuf};
+
+use crate::ValueSource;
+
+
+/// A problem that does not prevent loading the configuration, but that likely
+/// indicates a misconfiguration. Obtained via
+/// [`Builder::load_with_warnings`][crate::Builder::load_with_warnings].
+///
+/// The `Display` output is a human readable message, suitable for logging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// A key in a configuration file or a file in a key directory does not
+    /// correspond to any configuration value, and is thus ignored. In strict
+    /// mode (see [`Builder::strict`][crate::Builder::strict]), this is an
+    /// error instead.
+    UnknownKey {
+        /// The dotted path of the unknown key, e.g. `log.stdot`.
+        path: String,
+
+        /// The dotted path of a similarly named value, e.g. `log.stdout`.
+        suggestion: Option<String>,
+
+        /// Where the unknown key was found.
+        source: ValueSource,
+    },
+
+    /// An environment variable starts with the env prefix of the
+    /// configuration (`#[config(env_prefix = "...")]`), but does not
+    /// correspond to any configuration value.
+    UnknownEnvVar {
+        /// The variable, either [`ValueSource::Env`] or
+        /// [`ValueSource::EnvFile`].
+        source: ValueSource,
+
+        /// The key of a similarly named variable, e.g. `APP_PORT`.
+        suggestion: Option<String>,
+    },
+
+    /// A deprecated old name of a value (`#[config(alias = "...")]` or
+    /// `#[config(alias_env = "...")]`) was used.
+    DeprecatedAlias {
+        /// The old name as used in the source: the dotted path for files,
+        /// e.g. `log.old_name`, or the key of an environment variable.
+        alias: String,
+
+        /// The name that should be used instead, in the same format.
+        replacement: String,
+
+        /// The dotted path of the value.
+        path: String,
+
+        /// Where the old name was used.
+        source: ValueSource,
+    },
+
+    /// A value marked as deprecated (`#[config(deprecated = "...")]`) was
+    /// set. If the deprecated field is a nested one, this is emitted once per
+    /// source setting any value inside it.
+    Deprecated {
+        /// The dotted path of the deprecated field.
+        path: String,
+
+        /// The message of the `deprecated` attribute.
+        message: String,
+
+        /// The source that set the value.
+        source: ValueSource,
+    },
+
+    /// An optional configuration file or directory (e.g. added via
+    /// [`Builder::file`][crate::Builder::file]) does not exist.
+    MissingFile {
+        path: PathBuf,
+    },
+}
+
+impl Warning {
+    /// Returns the dotted path of the configuration value this warning is
+    /// about, if any.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::UnknownKey { path, .. }
+            | Self::DeprecatedAlias { path, .. }
+            | Self::Deprecated { path, .. } => Some(path),
+            Self::UnknownEnvVar { .. } | Self::MissingFile { .. } => None,
+        }
+    }
+
+    /// Returns the source in which the problem was found, if any.
+    pub fn source(&self) -> Option<&ValueSource> {
+        match self {
+            Self::UnknownKey { source, .. }
+            | Self::UnknownEnvVar { source, .. }
+            | Self::DeprecatedAlias { source, .. }
+            | Self::Deprecated { source, .. } => Some(source),
+            Self::MissingFile { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let suggestion = match self {
+            Self::UnknownKey { path, suggestion, source } => {
+                write!(f, "unknown configuration key `{path}` in {source}")?;
+                suggestion
+            }
+            Self::UnknownEnvVar { source, suggestion } => {
+                write!(f, "{source} does not correspond to any configuration value")?;
+                suggestion
+            }
+            Self::DeprecatedAlias { replacement, source, .. }
+                if matches!(source, ValueSource::Env { .. } | ValueSource::EnvFile { .. }) =>
+            {
+                return write!(f, "{source} is deprecated, use `{replacement}` instead");
+            }
+            Self::DeprecatedAlias { alias, replacement, source, .. } => {
+                return write!(f, "deprecated name `{alias}` used in {source}, \
+                    use `{replacement}` instead");
+            }
+            Self::Deprecated { path, message, source } => {
+                return write!(f, "deprecated value `{path}` set in {source}: {message}");
+            }
+            Self::MissingFile { path } => {
+                return write!(f, "optional configuration file '{}' does not exist", path.display());
+            }
+        };
+
+        if let Some(suggestion) = suggestion {
+            write!(f, " (did you mean `{suggestion}`?)")?;
+        }
+        Ok(())
+    }
+}
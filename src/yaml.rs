@@ -3,10 +3,13 @@
 
 use std::fmt::{self, Write};
 
+use serde::Serialize;
+
 use crate::{
     meta::Expr,
+    ser::Value,
     template::{self, Formatter},
-    Config,
+    Config, Error, Provenance,
 };
 
 
@@ -99,6 +102,41 @@ pub fn template<C: Config>(options: FormatOptions) -> String {
     out.finish()
 }
 
+/// Formats the given configuration as YAML, e.g. to show the effective
+/// configuration of your application.
+///
+/// Works like [`toml::to_string`][crate::toml::to_string], see its
+/// documentation for more information.
+pub fn to_string<C: Config + Serialize>(
+    config: &C,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    format_values(config, None, options)
+}
+
+/// Like [`to_string`], but additionally states the source of each value in a
+/// comment above it. See
+/// [`toml::to_string_with_provenance`][crate::toml::to_string_with_provenance]
+/// for more information.
+pub fn to_string_with_provenance<C: Config + Serialize>(
+    config: &C,
+    provenance: &Provenance,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    format_values(config, Some(provenance), options)
+}
+
+fn format_values<C: Config + Serialize>(
+    config: &C,
+    provenance: Option<&Provenance>,
+    options: FormatOptions,
+) -> Result<String, Error> {
+    let values = Value::from_serialize(config)?;
+    let mut out = YamlFormatter::new(&options);
+    template::format_values(&C::META, &values, provenance, &mut out, options.general)?;
+    Ok(out.finish())
+}
+
 struct YamlFormatter {
     indent: u8,
     buffer: String,
@@ -133,16 +171,27 @@ impl Formatter for YamlFormatter {
     }
 
     fn disabled_field(&mut self, name: &str, value: Option<&'static Expr>) {
+        let name = PrintKey(name);
         match value.map(PrintExpr) {
             None => self.comment(format_args!("{name}:")),
             Some(v) => self.comment(format_args!("{name}: {v}")),
         };
     }
 
+    fn field(&mut self, name: &'static str, value: &Value) -> Result<(), String> {
+        self.emit_indentation();
+        writeln!(self.buffer, "{}: {}", PrintKey(name), PrintValue(value)).unwrap();
+        Ok(())
+    }
+
+    fn redacted_field(&mut self, name: &'static str) {
+        self.comment(format_args!("{}: <redacted>", PrintKey(name)));
+    }
+
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]) {
         doc.iter().for_each(|doc| self.comment(doc));
         self.emit_indentation();
-        writeln!(self.buffer, "{name}:").unwrap();
+        writeln!(self.buffer, "{}:", PrintKey(name)).unwrap();
         self.depth += 1;
     }
 
@@ -205,23 +254,98 @@ impl fmt::Display for PrintExpr<'_> {
                 Ok(())
             }
 
+            Expr::Str(s) => PrintStr(s).fmt(f),
+
             // All these other types can simply be serialized as is.
-            Expr::Str(_) | Expr::Float(_) | Expr::Integer(_) | Expr::Bool(_) => {
-                let out = serde_yaml::to_string(&self.0)
-                    .expect("string serialization to YAML failed");
-
-                // Unfortunately, `serde_yaml` cannot serialize these values on its own
-                // without embedding them in a full document (starting with `---` and
-                // ending with a newline). So we need to cleanup.
-                out.strip_prefix("---\n")
-                    .unwrap_or(&out)
-                    .trim_matches('\n')
-                    .fmt(f)
+            Expr::Float(_) | Expr::Integer(_) | Expr::Bool(_) => fmt_scalar(self.0, f),
+        }
+    }
+}
+
+
+/// Helper to emit a serialized value into YAML, using the flow style for lists
+/// and maps like `PrintExpr`.
+struct PrintValue<'a>(&'a Value);
+
+impl fmt::Display for PrintValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Seq(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    PrintValue(item).fmt(f)?;
+                }
+                f.write_char(']')
+            }
+            Value::Map(entries) => {
+                f.write_str("{ ")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    PrintValue(key).fmt(f)?;
+                    f.write_str(": ")?;
+                    PrintValue(value).fmt(f)?;
+                }
+                f.write_str(" }")
+            }
+            Value::Str(s) => PrintStr(s).fmt(f),
+            scalar => fmt_scalar(scalar, f),
+        }
+    }
+}
+
+/// Helper to emit a key into YAML, quoted if it is not a plain scalar.
+struct PrintKey<'a>(&'a str);
+
+impl fmt::Display for PrintKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        PrintStr(self.0).fmt(f)
+    }
+}
+
+/// Helper to emit a string into YAML. Multi-line strings are emitted as double
+/// quoted scalars, as `serde_yaml` would emit a block scalar, whose lines are
+/// not indented according to the nesting level.
+struct PrintStr<'a>(&'a str);
+
+impl fmt::Display for PrintStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.0.contains(['\n', '\r']) {
+            return fmt_scalar(self.0, f);
+        }
+
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                    write!(f, "\\u{:04x}", c as u32)?;
+                }
+                c => f.write_char(c)?,
             }
         }
+        f.write_char('"')
     }
 }
 
+/// Emits a scalar value via `serde_yaml`.
+fn fmt_scalar<T: Serialize + ?Sized>(value: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let out = serde_yaml::to_string(value).expect("string serialization to YAML failed");
+
+    // Unfortunately, `serde_yaml` cannot serialize these values on its own
+    // without embedding them in a full document (starting with `---` and
+    // ending with a newline). So we need to cleanup.
+    f.write_str(out.strip_prefix("---\n").unwrap_or(&out).trim_matches('\n'))
+}
+
 
 #[cfg(test)]
 mod tests {
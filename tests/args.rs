@@ -0,0 +1,134 @@
+use pretty_assertions::assert_eq;
+
+use confique::{Config, ValueSource};
+
+
+#[derive(Debug, Config)]
+/// My app.
+struct Conf {
+    /// Name of the site.
+    #[config(env = "ARGS_TEST_NAME")]
+    name: String,
+
+    #[config(nested)]
+    log: LogConf,
+}
+
+#[derive(Debug, Config)]
+struct LogConf {
+    /// Whether to log to stdout.
+    #[config(default = true)]
+    stdout: bool,
+
+    #[config(default = ["a", "b"])]
+    targets: Vec<String>,
+
+    max_file_size: Option<u64>,
+}
+
+#[test]
+fn values() {
+    let conf = Conf::builder()
+        .args(["app", "--name", "peter", "--log.stdout=no", "--log.max-file-size", "300"])
+        .load()
+        .unwrap();
+    assert_eq!(conf.name, "peter");
+    assert!(!conf.log.stdout);
+    assert_eq!(conf.log.targets, ["a", "b"]);
+    assert_eq!(conf.log.max_file_size, Some(300));
+}
+
+#[test]
+fn implicit_true_and_last_wins() {
+    let conf = Conf::builder()
+        .args(["app", "--name=a", "--log.stdout=0", "--name=b", "--log.stdout"])
+        .load()
+        .unwrap();
+    assert_eq!(conf.name, "b");
+    assert!(conf.log.stdout);
+}
+
+#[test]
+fn layering() {
+    let (conf, provenance) = Conf::builder()
+        .args(["app", "--log.stdout", "false", "--log.max-file-size=30"])
+        .env_from([
+            ("ARGS_TEST_NAME".to_string(), "anna".to_string()),
+        ])
+        .load_with_provenance()
+        .unwrap();
+    assert_eq!(conf.name, "anna");
+    assert!(!conf.log.stdout);
+    assert_eq!(conf.log.max_file_size, Some(30));
+    let arg = |name: &str| Some(ValueSource::Arg { name: name.into() });
+    assert_eq!(provenance.get("log.stdout").cloned(), arg("--log.stdout"));
+    assert_eq!(provenance.get("log.max_file_size").cloned(), arg("--log.max-file-size"));
+    assert_eq!(provenance.get("name"), Some(&ValueSource::Env { key: "ARGS_TEST_NAME".into() }));
+}
+
+#[test]
+fn errors() {
+    let load = |args: &[&str]| {
+        Conf::builder().args(args.iter().copied()).load().unwrap_err().to_string()
+    };
+
+    assert_eq!(
+        load(&["app", "--nope=3"]),
+        "invalid command line argument `--nope=3`: unknown configuration value",
+    );
+    assert_eq!(
+        load(&["app", "--log.stdot"]),
+        "invalid command line argument `--log.stdot`: unknown configuration value \
+            (did you mean `--log.stdout`?)",
+    );
+    assert_eq!(
+        load(&["app", "name"]),
+        "invalid command line argument `name`: expected an option starting with `--`",
+    );
+    assert_eq!(
+        load(&["app", "--log.max_file_size=-1"]),
+        "failed to deserialize value `log.max_file_size` from command line argument \
+            `--log.max_file_size`: invalid value '-1' for type u64: invalid digit found in string",
+    );
+    assert_eq!(
+        load(&["app", "--log.max_file_size"]),
+        "invalid command line argument `--log.max_file_size`: missing value",
+    );
+
+    // Only `bool` fields have an implicit value.
+    assert_eq!(
+        load(&["app", "--name", "--log.stdout"]),
+        "invalid command line argument `--name`: missing value",
+    );
+}
+
+#[test]
+fn help() {
+    let err = Conf::builder().args(["/usr/bin/app", "--name=x", "--help"]).load().unwrap_err();
+    assert!(err.is_help_request());
+    assert_eq!(err.to_string(), "\
+My app.
+
+Usage: app [OPTIONS]
+
+Options:
+  --name <VALUE>
+      Name of the site.
+      [env: ARGS_TEST_NAME] [required]
+
+  --log.stdout <VALUE>
+      Whether to log to stdout.
+      [default: true]
+
+  --log.targets <VALUE>
+      [default: [a, b]]
+
+  --log.max_file_size <VALUE>
+
+  -h, --help
+      Print this help and exit.
+");
+
+    let err = Conf::builder().args(["app", "--nope"]).load().unwrap_err();
+    assert!(!err.is_help_request());
+}
@@ -15,12 +15,24 @@ fn vec_u32() {
     assert_eq!(Foo::META, meta::Meta {
         name: "Foo",
         doc: &[],
+        env_prefix: None,
         fields: &[
             meta::Field {
                 name: "bar",
                 doc: &[" A nice doc comment."],
+                secret: false,
+                aliases: &[],
+                deprecated: None,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    env_aliases: &[],
+                    env_file_suffix: false,
+                    credential: None,
+                    constraints: &[],
+                    ty: meta::Type::Array(&meta::Type::Integer {
+                        min: meta::Integer::U32(u32::MIN),
+                        max: meta::Integer::U32(u32::MAX),
+                    }),
                     kind: meta::LeafKind::Required {
                         default: Some(meta::Expr::Array(&[
                             meta::Expr::Integer(meta::Integer::U32(1)),
@@ -64,6 +76,11 @@ fn inferred_type() {
         match actual {
             meta::FieldKind::Leaf {
                 env: None,
+                env_aliases: &[],
+                env_file_suffix: false,
+                credential: None,
+                constraints: &[],
+                ty: _,
                 kind: meta::LeafKind::Required {
                     default: Some(meta::Expr::Array(items)),
                 },
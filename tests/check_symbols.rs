@@ -8,6 +8,7 @@ use confique::Config;
 
 
 #[derive(Debug, Config)]
+#[config(serialize)]
 /// A sample configuration for our app.
 struct Conf {
     #[config(nested)]
@@ -18,6 +19,7 @@ struct Conf {
 
 /// Configuring the HTTP server of our app.
 #[derive(Debug, Config)]
+#[config(serialize)]
 struct Http {
     /// The port the server will listen on.
     #[config(env = "PORT")]
@@ -31,3 +33,9 @@ struct Http {
 
 #[test]
 fn compiles() {}
+
+#[derive(Config)]
+struct WithSecret {
+    #[config(secret)]
+    token: std::string::String,
+}
@@ -0,0 +1,42 @@
+//! Helpers shared by the integration tests, included via `mod common;`. Not
+//! every test file uses all of them.
+#![allow(dead_code)]
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+
+/// Returns a new, empty directory in the temporary directory. `name` has to
+/// be unique among all tests (e.g. `<test file>-<test>`), as a previous
+/// directory with that name is removed.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("confique-test-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Like [`temp_dir`], but additionally creates the given files (name and
+/// content) in the directory.
+pub fn create_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = temp_dir(name);
+    for (file, content) in files {
+        fs::write(dir.join(file), content).unwrap();
+    }
+    dir
+}
+
+/// Writes `content` to the file `name` in a directory shared by all tests of
+/// the test file `test`, and returns its path. The directory is not cleared,
+/// so file names have to be unique within the test file.
+pub fn write_file(test: &str, name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("confique-test-{test}"));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// Environment variables for `Builder::env_from` or as an `EnvSource`.
+pub fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
@@ -0,0 +1,146 @@
+use pretty_assertions::assert_eq;
+
+use confique::{meta, Config, Partial};
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(nested)]
+    http: HttpConf,
+
+    #[config(default = "info", one_of = ["debug", "info", "warn"])]
+    log_level: String,
+
+    #[config(non_empty)]
+    name: Option<String>,
+}
+
+#[derive(Debug, Config)]
+struct HttpConf {
+    /// Port to listen on.
+    #[config(default = 8080, min = 1, max = 65535)]
+    port: u32,
+
+    #[config(default = 0.5, min = 0.0)]
+    ratio: f64,
+
+    #[config(default = ["localhost"], non_empty)]
+    hosts: Vec<String>,
+}
+
+fn load(overrides: &[&str]) -> Result<Conf, confique::Error> {
+    Conf::builder().overrides(overrides.iter().copied()).load()
+}
+
+#[test]
+fn valid() {
+    let conf = load(&["http.port=1", "log_level=warn", "name=foo"]).unwrap();
+    assert_eq!(conf.http.port, 1);
+    assert_eq!(conf.http.ratio, 0.5);
+    assert_eq!(conf.http.hosts, ["localhost"]);
+    assert_eq!(conf.log_level, "warn");
+    assert_eq!(conf.name.as_deref(), Some("foo"));
+
+    let conf = load(&["http.port=65535"]).unwrap();
+    assert_eq!(conf.http.port, 65535);
+    assert_eq!(conf.name, None);
+}
+
+#[test]
+fn invalid() {
+    let err = |overrides: &[&str]| load(overrides).unwrap_err().to_string();
+
+    assert_eq!(
+        err(&["http.port=0"]),
+        "invalid configuration value `http.port`: must be at least 1",
+    );
+    assert_eq!(
+        err(&["http.port=65536"]),
+        "invalid configuration value `http.port`: must be at most 65535",
+    );
+    assert_eq!(
+        err(&["http.ratio=-0.1"]),
+        "invalid configuration value `http.ratio`: must be at least 0.0",
+    );
+    assert_eq!(
+        err(&["log_level=trace"]),
+        "invalid configuration value `log_level`: must be one of: \"debug\", \"info\", \"warn\"",
+    );
+    assert_eq!(
+        err(&["name="]),
+        "invalid configuration value `name`: must not be empty",
+    );
+
+    let mut partial = <Conf as Config>::Partial::empty();
+    partial.http.hosts = Some(vec![]);
+    assert_eq!(
+        Conf::builder().preloaded(partial).load().unwrap_err().to_string(),
+        "invalid configuration value `http.hosts`: must not be empty",
+    );
+}
+
+#[test]
+fn meta() {
+    let meta::FieldKind::Nested { meta: http, .. } = Conf::META.fields[0].kind else {
+        panic!("expected nested field");
+    };
+    assert_eq!(http.fields[0].kind, meta::FieldKind::Leaf {
+        env: None,
+        env_aliases: &[],
+        env_file_suffix: false,
+        credential: None,
+        constraints: &[
+            meta::Constraint::Min(meta::Expr::Integer(meta::Integer::U32(1))),
+            meta::Constraint::Max(meta::Expr::Integer(meta::Integer::U32(65535))),
+        ],
+        ty: meta::Type::Integer {
+            min: meta::Integer::U32(u32::MIN),
+            max: meta::Integer::U32(u32::MAX),
+        },
+        kind: meta::LeafKind::Required {
+            default: Some(meta::Expr::Integer(meta::Integer::U32(8080))),
+        },
+    });
+    assert_eq!(Conf::META.fields[1].kind, meta::FieldKind::Leaf {
+        env: None,
+        env_aliases: &[],
+        env_file_suffix: false,
+        credential: None,
+        constraints: &[meta::Constraint::OneOf(&[
+            meta::Expr::Str("debug"),
+            meta::Expr::Str("info"),
+            meta::Expr::Str("warn"),
+        ])],
+        ty: meta::Type::String,
+        kind: meta::LeafKind::Required { default: Some(meta::Expr::Str("info")) },
+    });
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn toml_template() {
+    let template = confique::toml::template::<Conf>(Default::default());
+    assert_eq!(template, concat!(
+        "# Default value: \"info\"\n",
+        "# Allowed values: \"debug\", \"info\", \"warn\"\n",
+        "#log_level = \"info\"\n",
+        "\n",
+        "# Must not be empty.\n",
+        "#name =\n",
+        "\n",
+        "[http]\n",
+        "# Port to listen on.\n",
+        "#\n",
+        "# Default value: 8080\n",
+        "# Range: 1..=65535\n",
+        "#port = 8080\n",
+        "\n",
+        "# Default value: 0.5\n",
+        "# Minimum: 0\n",
+        "#ratio = 0.5\n",
+        "\n",
+        "# Default value: [\"localhost\"]\n",
+        "# Must not be empty.\n",
+        "#hosts = [\"localhost\"]\n",
+    ));
+}
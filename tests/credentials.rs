@@ -0,0 +1,101 @@
+use std::fs;
+
+use pretty_assertions::assert_eq;
+
+use confique::{meta, Config, ValueSource};
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(nested)]
+    db: DbConf,
+}
+
+#[derive(Debug, Config)]
+struct DbConf {
+    #[config(default = "localhost")]
+    host: String,
+
+    /// Password of the database user.
+    #[config(env = "CREDENTIALS_TEST_DB_PASSWORD", credential = "db-password")]
+    password: String,
+
+    #[config(credential = "db-port")]
+    port: Option<u16>,
+}
+
+#[test]
+fn meta() {
+    assert!(matches!(
+        DbConf::META.fields[1].kind,
+        meta::FieldKind::Leaf { credential: Some("db-password"), .. },
+    ));
+    assert!(matches!(
+        DbConf::META.fields[0].kind,
+        meta::FieldKind::Leaf { credential: None, .. },
+    ));
+}
+
+#[test]
+fn load() {
+    let dir = std::env::temp_dir().join("confique-credentials-test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("db-password"), "hunter2\n").unwrap();
+
+    // Missing credential files are skipped.
+    let err = Conf::builder()
+        .systemd_credentials_from(dir.join("nope"))
+        .load()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'db.password'");
+
+    let (conf, provenance) = Conf::builder()
+        .systemd_credentials_from(&dir)
+        .load_with_provenance()
+        .unwrap();
+    assert_eq!(conf.db.host, "localhost");
+    assert_eq!(conf.db.password, "hunter2");
+    assert_eq!(conf.db.port, None);
+    assert_eq!(
+        provenance.get("db.password"),
+        Some(&ValueSource::Credential { name: "db-password".into() }),
+    );
+
+    fs::write(dir.join("db-port"), "many").unwrap();
+    let err = Conf::builder().systemd_credentials_from(&dir).load().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "failed to deserialize value `db.port` from file '{}': \
+                invalid value 'many' for type u16: invalid digit found in string",
+            dir.join("db-port").display(),
+        ),
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn template() {
+    use confique::toml::{self, FormatOptions};
+
+    let out = toml::template::<Conf>(FormatOptions::default());
+    assert_eq!(out, "\
+[db]
+# Default value: \"localhost\"
+#host = \"localhost\"
+
+# Password of the database user.
+#
+# Can also be specified via environment variable `CREDENTIALS_TEST_DB_PASSWORD`.
+# Can also be specified via systemd credential `db-password`.
+#
+# Required! This value must be specified.
+#password =
+
+# Can also be specified via systemd credential `db-port`.
+#port =
+");
+}
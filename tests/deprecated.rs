@@ -0,0 +1,165 @@
+#![cfg(feature = "toml")]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use confique::{meta, toml::FormatOptions, Config, ValueSource, Warning};
+use common::{vars, write_file};
+
+
+#[derive(Debug, Config)]
+#[config(env_prefix = "APP_")]
+struct Conf {
+    /// The port to listen on.
+    #[config(default = 8080, alias = "listen_port", alias_env = "LISTEN_PORT")]
+    port: u16,
+
+    #[config(deprecated = "has no effect anymore")]
+    workers: Option<u32>,
+
+    #[config(nested, alias = "logging")]
+    log: LogConf,
+
+    #[config(nested, deprecated = "use `log` instead")]
+    old_log: LogConf,
+}
+
+#[derive(Debug, Config)]
+struct LogConf {
+    #[config(default = false, alias = "stdout_enabled")]
+    stdout: bool,
+}
+
+#[test]
+fn meta() {
+    assert_eq!(Conf::META.fields[0].aliases, &["listen_port"]);
+    assert_eq!(Conf::META.fields[0].deprecated, None);
+    assert!(matches!(
+        Conf::META.fields[0].kind,
+        meta::FieldKind::Leaf { env_aliases: &["LISTEN_PORT"], .. },
+    ));
+    assert_eq!(Conf::META.fields[1].deprecated, Some("has no effect anymore"));
+    assert_eq!(Conf::META.fields[2].aliases, &["logging"]);
+}
+
+#[test]
+fn aliases_in_file() {
+    let content = "listen_port = 80\n[logging]\nstdout_enabled = true";
+    let path = write_file("deprecated", "aliases.toml", content);
+    let (conf, warnings) = Conf::builder().file(&path).load_with_warnings().unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(conf.log.stdout, true);
+    assert_eq!(conf.workers, None);
+    assert_eq!(conf.old_log.stdout, false);
+    assert_eq!(warnings, [
+        Warning::DeprecatedAlias {
+            alias: "listen_port".into(),
+            replacement: "port".into(),
+            path: "port".into(),
+            source: ValueSource::File(path.clone()),
+        },
+        Warning::DeprecatedAlias {
+            alias: "logging".into(),
+            replacement: "log".into(),
+            path: "log".into(),
+            source: ValueSource::File(path.clone()),
+        },
+        Warning::DeprecatedAlias {
+            alias: "logging.stdout_enabled".into(),
+            replacement: "log.stdout".into(),
+            path: "log.stdout".into(),
+            source: ValueSource::File(path.clone()),
+        },
+    ]);
+    assert_eq!(
+        warnings[0].to_string(),
+        format!(
+            "deprecated name `listen_port` used in file '{}', use `port` instead",
+            path.display(),
+        ),
+    );
+
+    // Aliases are accepted in strict mode.
+    Conf::builder().file(&path).strict().load().unwrap();
+}
+
+#[test]
+fn env_aliases() {
+    let (conf, warnings) = Conf::builder()
+        .env_from(vars(&[("APP_LISTEN_PORT", "80")]))
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(warnings, [Warning::DeprecatedAlias {
+        alias: "APP_LISTEN_PORT".into(),
+        replacement: "APP_PORT".into(),
+        path: "port".into(),
+        source: ValueSource::Env { key: "APP_LISTEN_PORT".into() },
+    }]);
+    assert_eq!(
+        warnings[0].to_string(),
+        "environment variable `APP_LISTEN_PORT` is deprecated, use `APP_PORT` instead",
+    );
+
+    // The new key has priority and the alias is then ignored.
+    let (conf, warnings) = Conf::builder()
+        .env_from(vars(&[("APP_LISTEN_PORT", "80"), ("APP_PORT", "90")]))
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(conf.port, 90);
+    assert_eq!(warnings, []);
+}
+
+#[test]
+fn deprecated_values() {
+    let path = write_file("deprecated", "deprecated.toml", "workers = 4\n[old_log]\nstdout = true");
+    let (conf, warnings) = Conf::builder()
+        .env_from(vars(&[("APP_WORKERS", "2")]))
+        .file(&path)
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(conf.workers, Some(2));
+    assert_eq!(conf.old_log.stdout, true);
+    assert_eq!(warnings, [
+        Warning::Deprecated {
+            path: "workers".into(),
+            message: "has no effect anymore".into(),
+            source: ValueSource::Env { key: "APP_WORKERS".into() },
+        },
+        Warning::Deprecated {
+            path: "workers".into(),
+            message: "has no effect anymore".into(),
+            source: ValueSource::File(path.clone()),
+        },
+        Warning::Deprecated {
+            path: "old_log".into(),
+            message: "use `log` instead".into(),
+            source: ValueSource::File(path.clone()),
+        },
+    ]);
+    assert_eq!(
+        warnings[0].to_string(),
+        "deprecated value `workers` set in environment variable `APP_WORKERS`: \
+            has no effect anymore",
+    );
+
+    // Unset deprecated values do not result in warnings.
+    let (_, warnings) = Conf::builder().load_with_warnings().unwrap();
+    assert_eq!(warnings, []);
+}
+
+#[test]
+fn template() {
+    let out = confique::toml::template::<Conf>(FormatOptions::default());
+    assert!(out.contains(
+        "# The port to listen on.\n\
+        #\n\
+        # Deprecated names: `listen_port`\n\
+        #\n\
+        # Can also be specified via environment variable `APP_PORT`.\n\
+        # Deprecated environment variables: `APP_LISTEN_PORT`\n",
+    ), "{out}");
+    assert!(out.contains("# Deprecated: has no effect anymore\n"), "{out}");
+    assert!(out.contains("# Deprecated names: `stdout_enabled`\n"), "{out}");
+}
@@ -0,0 +1,77 @@
+#![cfg(all(feature = "toml", feature = "yaml"))]
+
+mod common;
+
+use std::fs;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, ValueSource};
+use common::create_dir;
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    name: String,
+
+    #[config(default = 8080)]
+    port: u16,
+
+    #[config(default = "info")]
+    log_level: String,
+}
+
+#[test]
+fn later_files_win() {
+    let dir = create_dir("dir-order", &[
+        ("10-base.toml", "name = \"base\"\nport = 1000\nlog_level = \"warn\""),
+        ("30-local.yaml", "port: 3000"),
+        ("20-site.toml", "name = \"site\"\nport = 2000"),
+        ("50-ignored.txt", "garbage"),
+        (".40-hidden.toml", "garbage"),
+    ]);
+    // Subdirectories are ignored, even with a supported extension.
+    fs::create_dir(dir.join("20-subdir.toml")).unwrap();
+
+    let (conf, provenance) = Conf::builder()
+        .dir(&dir)
+        .load_with_provenance()
+        .unwrap();
+    assert_eq!(conf.name, "site");
+    assert_eq!(conf.port, 3000);
+    assert_eq!(conf.log_level, "warn");
+    assert_eq!(provenance.iter().collect::<Vec<_>>(), [
+        ("name", &ValueSource::File(dir.join("20-site.toml"))),
+        ("port", &ValueSource::File(dir.join("30-local.yaml"))),
+        ("log_level", &ValueSource::File(dir.join("10-base.toml"))),
+    ]);
+
+    // Sources added earlier still have a higher priority than the whole dir.
+    let conf = Conf::builder()
+        .overrides(["port=1"])
+        .dir(&dir)
+        .load()
+        .unwrap();
+    assert_eq!(conf.port, 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn errors() {
+    let dir = create_dir("dir-errors", &[("10-broken.toml", "port = \"x\"")]);
+    let err = Conf::builder().dir(&dir).load().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "failed to deserialize value `port` from file '{}' at line 1, column 8: \
+                invalid type: string \"x\", expected u16",
+            dir.join("10-broken.toml").display(),
+        ),
+    );
+    fs::remove_dir_all(&dir).unwrap();
+
+    let missing = std::env::temp_dir().join("confique-dir-test-does-not-exist");
+    let conf = Conf::builder().dir(missing).overrides(["name=x"]).load().unwrap();
+    assert_eq!(conf.port, 8080);
+}
@@ -1,5 +1,11 @@
+mod common;
+
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
-use confique::{Config};
+use confique::{meta, Config, Partial};
+use common::vars;
+
 
 #[derive(Debug, Deserialize)]
 enum Foo { A, B, C }
@@ -17,3 +23,289 @@ fn enum_env() {
     let conf = Conf::builder().env().load();
     assert!(matches!(conf, Ok(Conf { foo: Foo::B })));
 }
+
+#[test]
+fn from_env_source() {
+    #[derive(Config)]
+    #[allow(dead_code)]
+    struct Conf {
+        #[config(env = "FROM_ENV_SOURCE_TEST_FOO")]
+        foo: Foo,
+
+        #[config(env = "FROM_ENV_SOURCE_TEST_BAR")]
+        bar: Option<u32>,
+    }
+
+    let source: BTreeMap<_, _> = vars(&[("FROM_ENV_SOURCE_TEST_FOO", "C")]).into_iter().collect();
+    let partial = <Conf as Config>::Partial::from_env_source(&source).unwrap();
+    assert!(matches!(partial.foo, Some(Foo::C)));
+    assert_eq!(partial.bar, None);
+
+    let err = Conf::builder()
+        .env_from(vars(&[("FROM_ENV_SOURCE_TEST_BAR", "x")]))
+        .load()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(err.to_string().contains("FROM_ENV_SOURCE_TEST_BAR"));
+}
+
+
+mod prefixed {
+    #![allow(dead_code)]
+
+    use super::*;
+
+    #[derive(Config)]
+    #[config(env_prefix = "PREFIX_TEST_")]
+    pub(crate) struct Conf {
+        pub(crate) name: String,
+
+        #[config(env = "LISTEN_PORT")]
+        pub(crate) port: u16,
+
+        #[config(nested)]
+        pub(crate) log: LogConf,
+
+        #[config(nested)]
+        pub(crate) db: DbConf,
+    }
+
+    #[derive(Config)]
+    pub(crate) struct LogConf {
+        pub(crate) file: Option<String>,
+
+        #[config(env = "VERBOSE")]
+        pub(crate) verbose: bool,
+    }
+
+    #[derive(Config)]
+    #[config(env_prefix = "DATABASE_")]
+    pub(crate) struct DbConf {
+        pub(crate) host: String,
+    }
+}
+
+#[test]
+fn env_prefix() {
+    use prefixed::*;
+
+    assert_eq!(Conf::META.env_prefix, Some("PREFIX_TEST_"));
+    // Automatic keys are derived at runtime, only explicit ones are in `META`.
+    assert!(matches!(Conf::META.fields[0].kind, meta::FieldKind::Leaf { env: None, .. }));
+    assert_eq!(Conf::META.env_keys()[0], ("name".into(), "PREFIX_TEST_NAME".into()));
+
+    let conf = Conf::builder()
+        .env_from(vars(&[
+            ("PREFIX_TEST_NAME", "peter"),
+            ("PREFIX_TEST_LISTEN_PORT", "8080"),
+            ("PREFIX_TEST_LOG_FILE", "/tmp/log"),
+            ("PREFIX_TEST_LOG_VERBOSE", "yes"),
+            ("PREFIX_TEST_DATABASE_HOST", "localhost"),
+        ]))
+        .load()
+        .unwrap();
+    assert_eq!(conf.name, "peter");
+    assert_eq!(conf.port, 8080);
+    assert_eq!(conf.log.file.as_deref(), Some("/tmp/log"));
+    assert!(conf.log.verbose);
+    assert_eq!(conf.db.host, "localhost");
+
+    // Used on its own, `DbConf` uses its own prefix only.
+    let db = DbConf::builder()
+        .env_from(vars(&[("DATABASE_HOST", "example.com")]))
+        .load()
+        .unwrap();
+    assert_eq!(db.host, "example.com");
+
+    #[cfg(feature = "toml")]
+    {
+        let toml = confique::toml::template::<Conf>(Default::default());
+        assert!(toml.contains("`PREFIX_TEST_LOG_FILE`"));
+        assert!(toml.contains("`PREFIX_TEST_LOG_VERBOSE`"));
+        assert!(toml.contains("`PREFIX_TEST_DATABASE_HOST`"));
+    }
+}
+
+mod use_site_prefix {
+    #![allow(dead_code)]
+
+    use super::*;
+
+    #[derive(Config)]
+    pub(crate) struct Conf {
+        #[config(nested)]
+        pub(crate) primary: DbConf,
+
+        #[config(nested, env_prefix = "USE_SITE_TEST_REPLICA_")]
+        pub(crate) replica: DbConf,
+    }
+
+    #[derive(Config)]
+    pub(crate) struct DbConf {
+        #[config(env = "USE_SITE_TEST_HOST")]
+        pub(crate) host: String,
+
+        #[config(env = "USE_SITE_TEST_PORT", default = 5432)]
+        pub(crate) port: u16,
+    }
+}
+
+#[test]
+fn nested_env_prefix() {
+    use use_site_prefix::*;
+
+    assert!(matches!(
+        Conf::META.fields[1].kind,
+        meta::FieldKind::Nested { env_prefix: Some("USE_SITE_TEST_REPLICA_"), .. },
+    ));
+    assert_eq!(Conf::META.env_keys(), [
+        ("primary.host".to_string(), "USE_SITE_TEST_HOST".to_string()),
+        ("primary.port".to_string(), "USE_SITE_TEST_PORT".to_string()),
+        ("replica.host".to_string(), "USE_SITE_TEST_REPLICA_USE_SITE_TEST_HOST".to_string()),
+        ("replica.port".to_string(), "USE_SITE_TEST_REPLICA_USE_SITE_TEST_PORT".to_string()),
+    ]);
+
+    let conf = Conf::builder()
+        .env_from(vars(&[
+            ("USE_SITE_TEST_HOST", "primary.local"),
+            ("USE_SITE_TEST_REPLICA_USE_SITE_TEST_HOST", "replica.local"),
+            ("USE_SITE_TEST_REPLICA_USE_SITE_TEST_PORT", "5433"),
+        ]))
+        .load()
+        .unwrap();
+    assert_eq!(conf.primary.host, "primary.local");
+    assert_eq!(conf.primary.port, 5432);
+    assert_eq!(conf.replica.host, "replica.local");
+    assert_eq!(conf.replica.port, 5433);
+
+    #[cfg(feature = "toml")]
+    {
+        let toml = confique::toml::template::<Conf>(Default::default());
+        assert!(toml.contains("`USE_SITE_TEST_HOST`"));
+        assert!(toml.contains("`USE_SITE_TEST_REPLICA_USE_SITE_TEST_HOST`"));
+    }
+}
+
+#[test]
+fn env_file() {
+    #[derive(Debug, Config)]
+    struct Conf {
+        #[config(env = "ENV_FILE_TEST_NAME")]
+        name: String,
+
+        #[config(env = "ENV_FILE_TEST_PORT")]
+        port: u16,
+
+        #[config(env = "ENV_FILE_TEST_DEBUG", default = false)]
+        debug: bool,
+    }
+
+    let path = std::env::temp_dir().join("confique-env-file-test.env");
+    std::fs::write(&path, "\
+        # Local settings\n\
+        export ENV_FILE_TEST_NAME=\"peter # not a comment\"\n\
+        ENV_FILE_TEST_PORT = 8080 # a comment\n\
+        ENV_FILE_TEST_DEBUG='yes'\n\
+    ").unwrap();
+
+    let (conf, provenance) = Conf::builder()
+        .env_file(&path)
+        .load_with_provenance()
+        .unwrap();
+    assert_eq!(conf.name, "peter # not a comment");
+    assert_eq!(conf.port, 8080);
+    assert!(conf.debug);
+    assert_eq!(provenance.get("port"), Some(&confique::ValueSource::EnvFile {
+        path: path.clone(),
+        key: "ENV_FILE_TEST_PORT".into(),
+    }));
+    assert!(std::env::var("ENV_FILE_TEST_NAME").is_err());
+
+    std::fs::write(&path, "ENV_FILE_TEST_PORT=8080\nnonsense\n").unwrap();
+    let err = Conf::builder().env_file(&path).load().unwrap_err();
+    assert_eq!(
+        std::error::Error::source(&err).unwrap().to_string(),
+        "line 2: expected `KEY=VALUE`",
+    );
+
+    std::fs::remove_file(&path).unwrap();
+    let missing = Conf::builder().env_file(&path).load().unwrap_err();
+    assert_eq!(missing.to_string(), "2 errors occured while loading the configuration:\n\
+        - required configuration value is missing: 'name'\n\
+        - required configuration value is missing: 'port'");
+}
+
+#[test]
+fn env_file_suffix() {
+    #[derive(Debug, Config)]
+    struct Conf {
+        #[config(env = "DB_PASS", env_file_suffix)]
+        password: String,
+
+        #[config(env = "DB_USER")]
+        user: Option<String>,
+    }
+
+    let dir = std::env::temp_dir();
+    let pass_path = dir.join("confique-env-file-suffix-pass");
+    let user_path = dir.join("confique-env-file-suffix-user");
+    std::fs::write(&pass_path, "hunter2\n").unwrap();
+    std::fs::write(&user_path, "admin").unwrap();
+    let source = vars(&[
+        ("DB_PASS_FILE", pass_path.to_str().unwrap()),
+        ("DB_USER_FILE", user_path.to_str().unwrap()),
+    ]);
+
+    // The direct variable takes precedence.
+    let conf = Conf::builder()
+        .env_from(vars(&[("DB_PASS", "direct")]).into_iter().chain(source.clone()))
+        .load()
+        .unwrap();
+    assert_eq!(conf.password, "direct");
+
+    let conf = Conf::builder().env_from(source.clone()).load().unwrap();
+    assert_eq!(conf.password, "hunter2");
+    assert_eq!(conf.user, None);
+
+    // Enabled for all fields via the builder.
+    let conf = Conf::builder().env_from(source.clone()).env_file_suffix().load().unwrap();
+    assert_eq!(conf.user.as_deref(), Some("admin"));
+
+    std::fs::remove_file(&pass_path).unwrap();
+    let err = Conf::builder().env_from(source).load().unwrap_err();
+    assert_eq!(err.to_string(), format!(
+        "IO error occured while reading file '{}' specified by environment variable \
+            `DB_PASS_FILE`",
+        pass_path.display(),
+    ));
+    std::fs::remove_file(&user_path).unwrap();
+
+    assert!(matches!(
+        Conf::META.fields[0].kind,
+        meta::FieldKind::Leaf { env_file_suffix: true, .. },
+    ));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn env_file_suffix_template() {
+    use confique::toml::{self, FormatOptions};
+
+    #[derive(Config)]
+    #[allow(dead_code)]
+    struct Conf {
+        /// Database password.
+        #[config(env = "DB_PASS", env_file_suffix)]
+        password: String,
+    }
+
+    assert_eq!(toml::template::<Conf>(FormatOptions::default()), "\
+# Database password.
+#
+# Can also be specified via environment variable `DB_PASS`.
+# Or via a file whose path is specified in environment variable `DB_PASS_FILE`.
+#
+# Required! This value must be specified.
+#password =
+");
+}
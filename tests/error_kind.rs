@@ -0,0 +1,109 @@
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, ErrorKind, SourceLayer};
+use common::vars;
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(env = "ERROR_KIND_TEST_PORT", min = 1)]
+    port: u16,
+
+    #[config(default = "localhost")]
+    host: String,
+}
+
+#[test]
+fn valid() {
+    let conf = Conf::builder().overrides(["port=80"]).load().unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(conf.host, "localhost");
+}
+
+#[test]
+fn missing_and_validation() {
+    let err = Conf::builder().load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingValue);
+    assert_eq!(err.field_path(), Some("port"));
+    assert_eq!(err.source_layer(), None);
+    assert_eq!(err.env_key(), None);
+    assert_eq!(err.file_path(), None);
+
+    let err = Conf::builder().overrides(["port=0"]).load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Validation);
+    assert_eq!(err.field_path(), Some("port"));
+    assert_eq!(err.source_layer(), None);
+}
+
+#[test]
+fn env() {
+    let err = Conf::builder()
+        .env_from(vars(&[("ERROR_KIND_TEST_PORT", "x")]))
+        .load()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidEnvValue);
+    assert_eq!(err.field_path(), Some("port"));
+    assert_eq!(err.env_key(), Some("ERROR_KIND_TEST_PORT"));
+    assert_eq!(err.source_layer(), Some(SourceLayer::Env));
+}
+
+#[test]
+fn args_and_overrides() {
+    let err = Conf::builder().args(["app", "--nope"]).load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidArg);
+    assert_eq!(err.source_layer(), Some(SourceLayer::Args));
+
+    let err = Conf::builder().overrides(["port=x"]).load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOverrideValue);
+    assert_eq!(err.field_path(), Some("port"));
+    assert_eq!(err.source_layer(), Some(SourceLayer::Overrides));
+}
+
+#[test]
+fn multiple() {
+    let err = Conf::builder()
+        .env_from(vars(&[("ERROR_KIND_TEST_PORT", "x")]))
+        .overrides(["host"])
+        .load()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Multiple);
+    assert_eq!(err.field_path(), None);
+    let kinds = err.errors()
+        .map(|e| (e.kind(), e.source_layer()))
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [
+        (ErrorKind::InvalidEnvValue, Some(SourceLayer::Env)),
+        (ErrorKind::InvalidOverride, Some(SourceLayer::Overrides)),
+    ]);
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn files() {
+    use std::path::Path;
+
+    let err = Conf::builder().file(Path::new("/nonexistent/conf.unknown")).load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnsupportedFileFormat);
+    assert_eq!(err.file_path(), Some(Path::new("/nonexistent/conf.unknown")));
+    assert_eq!(err.source_layer(), Some(SourceLayer::File));
+
+    let path = std::env::temp_dir().join("confique-error-kind-test.toml");
+    std::fs::write(&path, "port = \"x\"").unwrap();
+    let err = Conf::builder().file(&path).load().unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(err.kind(), ErrorKind::Deserialization);
+    assert_eq!(err.file_path(), Some(&*path));
+    assert_eq!(err.field_path(), Some("port"));
+    assert_eq!(err.source_layer(), Some(SourceLayer::File));
+
+    let err = confique::File::new(&path).unwrap()
+        .required()
+        .load::<<Conf as Config>::Partial>()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), ErrorKind::MissingRequiredFile);
+    assert_eq!(err.file_path(), Some(&*path));
+    assert_eq!(err.source_layer(), None);
+}
@@ -23,12 +23,24 @@ fn simple() {
     assert_eq!(Animals::META, meta::Meta {
         name: "Animals",
         doc: &[" Root doc comment banana."],
+        env_prefix: None,
         fields: &[
             meta::Field {
                 name: "cat",
                 doc: &[" Doc comment for cat."],
+                secret: false,
+                aliases: &[],
+                deprecated: None,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    env_aliases: &[],
+                    env_file_suffix: false,
+                    credential: None,
+                    constraints: &[],
+                    ty: meta::Type::Integer {
+                        min: meta::Integer::U32(u32::MIN),
+                        max: meta::Integer::U32(u32::MAX),
+                    },
                     kind: meta::LeafKind::Required {
                         default: Some(meta::Expr::Integer(meta::Integer::U32(8080))),
                     },
@@ -37,8 +49,16 @@ fn simple() {
             meta::Field {
                 name: "dog",
                 doc: &[" Doc comment for dog."],
+                secret: false,
+                aliases: &[],
+                deprecated: None,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    env_aliases: &[],
+                    env_file_suffix: false,
+                    credential: None,
+                    constraints: &[],
+                    ty: meta::Type::String,
                     kind: meta::LeafKind::Required {
                         default: None,
                     },
@@ -125,36 +145,66 @@ fn full() {
     assert_eq!(Conf::META, meta::Meta {
         name: "Conf",
         doc: &[" A sample configuration for our app."],
+        env_prefix: None,
         fields: &[
             meta::Field {
                 name: "app_name",
                 doc: &[" Leaf field on top level struct."],
+                secret: false,
+                aliases: &[],
+                deprecated: None,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    env_aliases: &[],
+                    env_file_suffix: false,
+                    credential: None,
+                    constraints: &[],
+                    ty: meta::Type::String,
                     kind: meta::LeafKind::Required { default: None },
                 },
             },
             meta::Field {
                 name: "normal",
                 doc: &[],
+                secret: false,
+                aliases: &[],
+                deprecated: None,
                 kind: meta::FieldKind::Nested {
+                    env_prefix: None,
                     meta: &meta::Meta {
                         name: "NormalTest",
                         doc: &[],
+                        env_prefix: None,
                         fields: &[
                             meta::Field {
                                 name: "required",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::String,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
                             meta::Field {
                                 name: "with_default",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::String,
                                     kind: meta::LeafKind::Required {
                                         default: Some(meta::Expr::Str("127.0.0.1")),
                                     },
@@ -163,8 +213,16 @@ fn full() {
                             meta::Field {
                                 name: "optional",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::String,
                                     kind: meta::LeafKind::Optional,
                                 },
                             },
@@ -175,24 +233,45 @@ fn full() {
             meta::Field {
                 name: "deserialize_with",
                 doc: &[],
+                secret: false,
+                aliases: &[],
+                deprecated: None,
                 kind: meta::FieldKind::Nested {
+                    env_prefix: None,
                     meta: &meta::Meta {
                         name: "DeserializeWithTest",
                         doc: &[" Testing the `deserialize_with` attribute!", " Multiline, wow!"],
+                        env_prefix: None,
                         fields: &[
                             meta::Field {
                                 name: "required",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::Any,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
                             meta::Field {
                                 name: "with_default",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::Any,
                                     kind: meta::LeafKind::Required {
                                         default: Some(meta::Expr::Str("peter")),
                                     },
@@ -201,16 +280,32 @@ fn full() {
                             meta::Field {
                                 name: "optional",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::Any,
                                     kind: meta::LeafKind::Optional,
                                 },
                             },
                             meta::Field {
                                 name: "with_env",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_0"),
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::Any,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
@@ -221,24 +316,48 @@ fn full() {
             meta::Field {
                 name: "env",
                 doc: &[" Doc comment on nested."],
+                secret: false,
+                aliases: &[],
+                deprecated: None,
                 kind: meta::FieldKind::Nested {
+                    env_prefix: None,
                     meta: &meta::Meta {
                         name: "EnvTest",
                         doc: &[" Doc comment on nested struct!"],
+                        env_prefix: None,
                         fields: &[
                             meta::Field {
                                 name: "required",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_1"),
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::String,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
                             meta::Field {
                                 name: "with_default",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_2"),
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::Integer {
+                                        min: meta::Integer::U16(u16::MIN),
+                                        max: meta::Integer::U16(u16::MAX),
+                                    },
                                     kind: meta::LeafKind::Required {
                                         default: Some(
                                             meta::Expr::Integer(meta::Integer::U16(8080))
@@ -249,16 +368,32 @@ fn full() {
                             meta::Field {
                                 name: "optional",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_3"),
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::String,
                                     kind: meta::LeafKind::Optional,
                                 },
                             },
                             meta::Field {
                                 name: "env_collection",
                                 doc: &[],
+                                secret: false,
+                                aliases: &[],
+                                deprecated: None,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_4"),
+                                    env_aliases: &[],
+                                    env_file_suffix: false,
+                                    credential: None,
+                                    constraints: &[],
+                                    ty: meta::Type::Any,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
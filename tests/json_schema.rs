@@ -0,0 +1,190 @@
+use std::{collections::HashMap, net::IpAddr, path::PathBuf};
+
+use pretty_assertions::assert_eq;
+
+use confique::{meta, schema::json_schema, Config};
+
+
+/// Application configuration.
+#[derive(Config)]
+#[allow(dead_code)]
+struct Conf {
+    /// The name of the app.
+    ///
+    /// Shown in the "title".
+    name: String,
+
+    #[config(default = 0.5, min = 0.0)]
+    ratio: f64,
+
+    #[config(secret, alias = "pw")]
+    password: String,
+
+    #[config(one_of = ["debug", "info"], default = "info")]
+    level: String,
+
+    #[config(nested)]
+    http: HttpConf,
+
+    #[config(deserialize_with = deserialize_any)]
+    custom: Option<u32>,
+}
+
+/// HTTP settings.
+#[derive(Config)]
+#[allow(dead_code)]
+#[config(rename_all = "kebab-case")]
+struct HttpConf {
+    #[config(default = "127.0.0.1")]
+    bind_addr: IpAddr,
+
+    #[config(default = 8080, min = 1)]
+    port: u16,
+
+    #[config(default = ["localhost"], non_empty)]
+    hosts: Vec<String>,
+
+    #[config(default = { "x": true })]
+    flags: HashMap<String, bool>,
+
+    #[config(deprecated = "use `bind-addr`")]
+    socket: Option<PathBuf>,
+}
+
+fn deserialize_any<'de, D: serde::Deserializer<'de>>(_: D) -> Result<u32, D::Error> {
+    Ok(0)
+}
+
+#[test]
+fn types() {
+    assert_eq!(
+        HttpConf::META.fields.iter().map(|f| match f.kind {
+            meta::FieldKind::Leaf { ty, .. } => ty,
+            _ => unreachable!(),
+        }).collect::<Vec<_>>(),
+        [
+            meta::Type::String,
+            meta::Type::Integer {
+                min: meta::Integer::U16(0),
+                max: meta::Integer::U16(65535),
+            },
+            meta::Type::Array(&meta::Type::String),
+            meta::Type::Map(&meta::Type::Bool),
+            meta::Type::String,
+        ],
+    );
+    assert!(matches!(Conf::META.fields[5].kind, meta::FieldKind::Leaf {
+        ty: meta::Type::Any,
+        ..
+    }));
+}
+
+#[test]
+fn full() {
+    assert_eq!(json_schema::<Conf>(), r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "Conf",
+  "description": "Application configuration.",
+  "type": "object",
+  "properties": {
+    "name": {
+      "description": "The name of the app.\n\nShown in the \"title\".",
+      "type": "string"
+    },
+    "ratio": {
+      "type": "number",
+      "minimum": 0,
+      "default": 0.5
+    },
+    "pw": {
+      "type": "string",
+      "writeOnly": true,
+      "deprecated": true
+    },
+    "password": {
+      "type": "string",
+      "writeOnly": true
+    },
+    "level": {
+      "type": "string",
+      "enum": ["debug", "info"],
+      "default": "info"
+    },
+    "http": {
+      "description": "HTTP settings.",
+      "type": "object",
+      "properties": {
+        "bind-addr": {
+          "type": "string",
+          "default": "127.0.0.1"
+        },
+        "port": {
+          "type": "integer",
+          "minimum": 1,
+          "maximum": 65535,
+          "default": 8080
+        },
+        "hosts": {
+          "type": "array",
+          "items": {
+            "type": "string"
+          },
+          "minItems": 1,
+          "default": ["localhost"]
+        },
+        "flags": {
+          "type": "object",
+          "additionalProperties": {
+            "type": "boolean"
+          },
+          "default": {
+            "x": true
+          }
+        },
+        "socket": {
+          "type": "string",
+          "deprecated": true
+        }
+      },
+      "additionalProperties": false
+    },
+    "custom": {}
+  },
+  "required": ["name"],
+  "allOf": [
+    {
+      "anyOf": [
+        {
+          "required": ["password"]
+        },
+        {
+          "required": ["pw"]
+        }
+      ]
+    }
+  ],
+  "additionalProperties": false
+}
+"#);
+}
+
+#[test]
+fn required_nested() {
+    #[derive(Config)]
+    #[allow(dead_code)]
+    struct Outer {
+        #[config(nested)]
+        inner: Inner,
+    }
+
+    let schema = json_schema::<Outer>();
+    let end = "  },\n  \"required\": [\"inner\"],\n  \"additionalProperties\": false\n}\n";
+    assert!(schema.ends_with(end), "{schema}");
+    assert!(schema.contains("      \"required\": [\"value\"],\n"), "{schema}");
+}
+
+#[derive(Config)]
+#[allow(dead_code)]
+struct Inner {
+    value: u8,
+}
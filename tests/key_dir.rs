@@ -0,0 +1,73 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, ValueSource};
+use common::create_dir;
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    password: String,
+
+    #[config(nested)]
+    log: LogConf,
+}
+
+#[derive(Debug, Config)]
+struct LogConf {
+    #[config(default = true)]
+    stdout: bool,
+
+    file: Option<PathBuf>,
+
+    #[config(default = 3)]
+    retention_days: u32,
+}
+
+#[test]
+fn values() {
+    let dir = create_dir("key-dir-values", &[
+        ("password", "hunter2 \n\n"),
+        ("log.stdout", "no\n"),
+        ("log__file", "/var/log/app.log\r\n"),
+        ("unrelated", "foo"),
+        (".hidden", "foo"),
+    ]);
+    // Directories (like the `..data` of Kubernetes volumes) are ignored.
+    fs::create_dir(dir.join("..data")).unwrap();
+
+    let (conf, provenance) = Conf::builder()
+        .key_dir(&dir)
+        .load_with_provenance()
+        .unwrap();
+    assert_eq!(conf.password, "hunter2 ");
+    assert!(!conf.log.stdout);
+    assert_eq!(conf.log.file, Some("/var/log/app.log".into()));
+    assert_eq!(conf.log.retention_days, 3);
+    assert_eq!(provenance.get("log.file"), Some(&ValueSource::KeyFile(dir.join("log__file"))));
+    assert_eq!(provenance.get("log.retention_days"), Some(&ValueSource::Default));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn errors() {
+    let dir = create_dir("key-dir-errors", &[("log.retention_days", "forever\n")]);
+    let err = Conf::builder().key_dir(&dir).load().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "failed to deserialize value `log.retention_days` from file '{}': \
+                invalid value 'forever' for type u32: invalid digit found in string",
+            dir.join("log.retention_days").display(),
+        ),
+    );
+    fs::remove_dir_all(&dir).unwrap();
+
+    let missing = std::env::temp_dir().join("confique-key-dir-test-does-not-exist");
+    let err = Conf::builder().key_dir(missing).load().unwrap_err();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'password'");
+}
@@ -0,0 +1,107 @@
+#![cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+
+use std::path::PathBuf;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, Error};
+
+
+#[derive(Debug, Config)]
+#[allow(dead_code)]
+struct Conf {
+    #[config(default = "localhost")]
+    host: String,
+
+    #[config(default = 8080)]
+    port: u16,
+}
+
+fn load(name: &str, content: &str) -> (PathBuf, Error) {
+    let path = std::env::temp_dir().join(format!("confique-location-test-{name}"));
+    std::fs::write(&path, content).unwrap();
+    let err = Conf::builder().file(&path).load().unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    (path, err)
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn toml() {
+    let (path, err) = load("a.toml", "host = \"example.com\"\nport = \"x\"\n");
+    let location = err.location().unwrap();
+    assert_eq!((location.line(), location.column()), (2, 8));
+    assert_eq!(location.span(), 28..31);
+    assert_eq!(
+        err.to_string(),
+        format!("failed to deserialize value `port` from file '{}' at line 2, column 8: \
+            invalid type: string \"x\", expected u16", path.display()),
+    );
+    assert_eq!(format!("{err:#}"), format!(
+        "failed to deserialize value `port` from file '{}' at line 2, column 8: \
+            invalid type: string \"x\", expected u16\n  \
+           |\n\
+         2 | port = \"x\"\n  \
+           |        ^^^",
+        path.display(),
+    ));
+    assert!(std::error::Error::source(&err).is_none());
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn yaml() {
+    let (path, err) = load("a.yaml", "host: example.com\nport: x\n");
+    let location = err.location().unwrap();
+    assert_eq!((location.line(), location.column()), (2, 7));
+    assert_eq!(format!("{err:#}"), format!(
+        "failed to deserialize value `port` from file '{}' at line 2, column 7: \
+            invalid type: string \"x\", expected u16\n  \
+           |\n\
+         2 | port: x\n  \
+           |       ^",
+        path.display(),
+    ));
+}
+
+#[test]
+#[cfg(feature = "json5")]
+fn json5() {
+    let (path, err) = load("a.json5", "{\n  host: \"a\",\n  port: [,\n}\n");
+    assert_eq!(format!("{err:#}"), format!(
+        "failed to deserialize configuration from file '{}' at line 3, column 10\n  \
+           |\n\
+         3 |   port: [,\n  \
+           |          ^",
+        path.display(),
+    ));
+}
+
+#[test]
+fn env_file() {
+    let path = std::env::temp_dir().join("confique-location-test.env");
+    std::fs::write(&path, "A=1\n\nnonsense\n").unwrap();
+    let err = Conf::builder().env_file(&path).load().unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(format!("{err:#}"), format!(
+        "failed to deserialize configuration from env file '{}' at line 3, column 1\n  \
+           |\n\
+         3 | nonsense\n  \
+           | ^",
+        path.display(),
+    ));
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn toml_crlf_end_of_line() {
+    let (path, err) = load("crlf.toml", "port = \"x\r\nhost = \"a\"\r\n");
+    assert_eq!(format!("{err:#}"), format!(
+        "failed to deserialize configuration from file '{}' at line 1, column 10\n  \
+           |\n\
+         1 | port = \"x\n  \
+           |          ^",
+        path.display(),
+    ));
+}
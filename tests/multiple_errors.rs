@@ -0,0 +1,90 @@
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, Partial};
+use common::vars;
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(env = "NAME")]
+    name: String,
+
+    #[config(nested)]
+    http: HttpConf,
+}
+
+#[derive(Debug, Config)]
+struct HttpConf {
+    #[config(env = "PORT", min = 1)]
+    port: u16,
+
+    #[config(env = "WORKERS", default = 4, max = 16)]
+    workers: u32,
+
+    #[config(env = "TIMEOUT")]
+    timeout: Option<u32>,
+}
+
+fn messages(err: &confique::Error) -> Vec<String> {
+    err.errors().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn all_missing_and_invalid_values() {
+    let err = Conf::builder().overrides(["http.workers=20"]).load().unwrap_err();
+    assert_eq!(messages(&err), [
+        "required configuration value is missing: 'name'",
+        "required configuration value is missing: 'http.port'",
+        "invalid configuration value `http.workers`: must be at most 16",
+    ]);
+    assert_eq!(err.to_string(), "3 errors occured while loading the configuration:\n\
+        - required configuration value is missing: 'name'\n\
+        - required configuration value is missing: 'http.port'\n\
+        - invalid configuration value `http.workers`: must be at most 16");
+
+    let err = Conf::builder().overrides(["name=x", "http.port=0"]).load().unwrap_err();
+    assert_eq!(messages(&err), ["invalid configuration value `http.port`: must be at least 1"]);
+    assert_eq!(err.errors().count(), 1);
+}
+
+#[test]
+fn all_env_errors() {
+    let source = vars(&[("NAME", "x"), ("PORT", "x"), ("WORKERS", "8"), ("TIMEOUT", "-1")]);
+    let err = <Conf as Config>::Partial::from_env_source(&source).err().unwrap();
+    assert_eq!(messages(&err), [
+        "failed to deserialize value `http.port` from environment variable `PORT`: \
+            invalid value 'x' for type u16: invalid digit found in string",
+        "failed to deserialize value `http.timeout` from environment variable `TIMEOUT`: \
+            invalid value '-1' for type u32: invalid digit found in string",
+    ]);
+}
+
+#[test]
+fn source_errors_hide_missing_values() {
+    // `PORT` fails to load, so `http.port` being missing is not reported, but
+    // the failed validation of a value from another source is.
+    let err = Conf::builder()
+        .env_from(vars(&[("PORT", "x")]))
+        .overrides(["http.workers=17"])
+        .load()
+        .unwrap_err();
+    assert_eq!(messages(&err), [
+        "failed to deserialize value `http.port` from environment variable `PORT`: \
+            invalid value 'x' for type u16: invalid digit found in string",
+        "invalid configuration value `http.workers`: must be at most 16",
+    ]);
+}
+
+#[test]
+fn valid() {
+    let conf = Conf::builder()
+        .env_from(vars(&[("NAME", "x"), ("PORT", "80"), ("TIMEOUT", "3")]))
+        .load()
+        .unwrap();
+    assert_eq!(conf.name, "x");
+    assert_eq!(conf.http.port, 80);
+    assert_eq!(conf.http.workers, 4);
+    assert_eq!(conf.http.timeout, Some(3));
+}
@@ -0,0 +1,73 @@
+use pretty_assertions::assert_eq;
+
+use confique::{Config, ValueSource};
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(env = "OVERRIDES_TEST_NAME")]
+    name: String,
+
+    #[config(nested)]
+    http: HttpConf,
+}
+
+#[derive(Debug, Config)]
+struct HttpConf {
+    #[config(default = 8080)]
+    port: u16,
+
+    #[config(default = false)]
+    use_tls: bool,
+}
+
+#[test]
+fn values() {
+    let (conf, provenance) = Conf::builder()
+        .overrides(["name=peter", "http.port = 80", "http.use-tls=yes", "http.port=90"])
+        .env_from([("OVERRIDES_TEST_NAME".to_string(), "anna".to_string())])
+        .load_with_provenance()
+        .unwrap();
+
+    assert_eq!(conf.name, "peter");
+    assert_eq!(conf.http.port, 90);
+    assert!(conf.http.use_tls);
+    assert_eq!(
+        provenance.get("http.port"),
+        Some(&ValueSource::Override { value: "http.port=90".into() }),
+    );
+    assert_eq!(
+        provenance.get("http.use_tls").unwrap().to_string(),
+        "override `http.use-tls=yes`",
+    );
+}
+
+#[test]
+fn errors() {
+    let load = |overrides: &[&str]| {
+        Conf::builder()
+            .overrides(overrides.iter().copied())
+            .load()
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert_eq!(
+        load(&["name"]),
+        "invalid configuration override `name`: expected `path=value`",
+    );
+    assert_eq!(
+        load(&["http.prot=3"]),
+        "invalid configuration override `http.prot=3`: unknown configuration value \
+            `http.prot` (did you mean `http.port`?)",
+    );
+    assert_eq!(
+        load(&["http=3"]),
+        "invalid configuration override `http=3`: unknown configuration value `http`",
+    );
+    assert_eq!(
+        load(&["http.port=x"]),
+        "failed to deserialize value `http.port` from override `http.port=x`: \
+            invalid value 'x' for type u16: invalid digit found in string",
+    );
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, Partial};
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(nested)]
+    http: HttpConf,
+}
+
+#[derive(Debug, Config)]
+struct HttpConf {
+    #[config(default = "localhost")]
+    host: String,
+
+    #[config(nested)]
+    tls: TlsConf,
+}
+
+#[derive(Debug, Config)]
+struct TlsConf {
+    #[config(env = "PATHS_TEST_TLS_PORT", default = 443)]
+    port: u16,
+}
+
+#[test]
+fn valid() {
+    let conf = Conf::builder().load().unwrap();
+    assert_eq!(conf.http.host, "localhost");
+    assert_eq!(conf.http.tls.port, 443);
+}
+
+#[test]
+fn env() {
+    let vars = HashMap::from([("PATHS_TEST_TLS_PORT".to_string(), "x".to_string())]);
+    let err = <Conf as Config>::Partial::from_env_source(&vars).err().unwrap();
+    assert_eq!(err.field_path(), Some("http.tls.port"));
+    assert_eq!(
+        err.to_string(),
+        "failed to deserialize value `http.tls.port` from environment variable \
+            `PATHS_TEST_TLS_PORT`: invalid value 'x' for type u16: invalid digit found in string",
+    );
+}
+
+#[test]
+fn missing_and_overrides() {
+    let err = Conf::builder().overrides(["http.tls.port=x"]).load().unwrap_err();
+    assert_eq!(err.field_path(), Some("http.tls.port"));
+
+    let mut partial = <Conf as Config>::Partial::empty();
+    partial.http.host = Some("a".into());
+    let err = Conf::from_partial(partial).unwrap_err();
+    assert_eq!(err.field_path(), Some("http.tls.port"));
+}
+
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+fn load_file(name: &str, content: &str, expected_path: &str) -> confique::Error {
+    let path = std::env::temp_dir().join(format!("confique-paths-test-{name}"));
+    std::fs::write(&path, content).unwrap();
+
+    // Loading the file directly, e.g. to use it as a preloaded layer, also
+    // reports the path.
+    let direct = confique::File::new(&path).unwrap()
+        .load::<<Conf as Config>::Partial>()
+        .err()
+        .unwrap();
+    assert_eq!(direct.field_path(), Some(expected_path));
+
+    let err = Conf::builder().file(&path).load().unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    err
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn toml() {
+    let content = "[http]\nhost = \"a\"\n\n[http.tls]\nport = \"x\"\n";
+    let err = load_file("a.toml", content, "http.tls.port");
+    assert_eq!(err.field_path(), Some("http.tls.port"));
+    assert!(err.to_string().starts_with("failed to deserialize value `http.tls.port` from file"));
+    assert!(err.to_string().ends_with(": invalid type: string \"x\", expected u16"), "{err}");
+
+    // If a nested section has the wrong type, the path refers to the section.
+    let err = load_file("b.toml", "[http]\ntls = 3\n", "http.tls");
+    assert_eq!(err.field_path(), Some("http.tls"));
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn no_stale_path() {
+    // Overrides are deserialized without tracking the path like for files. A
+    // failure there must not leak into the errors of a later file.
+    Conf::builder().overrides(["http.tls.port=x"]).load().unwrap_err();
+
+    let path = std::env::temp_dir().join("confique-paths-test-syntax.toml");
+    std::fs::write(&path, "[http\n").unwrap();
+    let err = confique::File::new(&path).unwrap()
+        .load::<<Conf as Config>::Partial>()
+        .err()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(err.field_path(), None);
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn yaml() {
+    let err = load_file("a.yaml", "http:\n  tls:\n    port: x\n", "http.tls.port");
+    assert_eq!(err.field_path(), Some("http.tls.port"));
+    assert!(err.to_string().ends_with(": invalid type: string \"x\", expected u16"), "{err}");
+}
+
+#[test]
+#[cfg(feature = "json5")]
+fn json5() {
+    let err = load_file("a.json5", "{ http: { tls: { port: \"x\" } } }", "http.tls.port");
+    assert_eq!(err.field_path(), Some("http.tls.port"));
+    assert!(err.to_string().ends_with(": error parsing number"), "{err}");
+}
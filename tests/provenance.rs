@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, Partial, ValueSource};
+
+
+#[derive(Debug, Config)]
+#[config(partial_attr(derive(Default)))]
+struct Conf {
+    #[config(env = "PROVENANCE_TEST_NAME")]
+    name: String,
+
+    #[config(nested)]
+    log: LogConf,
+}
+
+#[derive(Debug, Config)]
+#[config(partial_attr(derive(Default)))]
+struct LogConf {
+    #[config(default = true)]
+    stdout: bool,
+
+    #[config(default = "info")]
+    level: String,
+
+    file: Option<PathBuf>,
+}
+
+type PartialConf = <Conf as Config>::Partial;
+type PartialLogConf = <LogConf as Config>::Partial;
+
+#[test]
+fn set_paths() {
+    let partial = PartialConf {
+        name: Some("peter".into()),
+        log: PartialLogConf {
+            file: Some("/tmp/log".into()),
+            ..Default::default()
+        },
+    };
+    assert_eq!(partial.set_paths(), ["name", "log.file"]);
+    assert_eq!(PartialConf::default_values().set_paths(), ["log.stdout", "log.level"]);
+    assert!(PartialConf::empty().set_paths().is_empty());
+}
+
+#[test]
+fn layers() {
+    let first = PartialConf {
+        log: PartialLogConf {
+            level: Some("debug".into()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let second = PartialConf {
+        name: Some("peter".into()),
+        log: PartialLogConf {
+            level: Some("trace".into()),
+            file: Some("/tmp/log".into()),
+            ..Default::default()
+        },
+    };
+
+    let (conf, provenance) = Conf::builder()
+        .preloaded(first)
+        .env_from([("PROVENANCE_TEST_NAME".to_owned(), "anna".to_owned())])
+        .preloaded(second)
+        .load_with_provenance()
+        .unwrap();
+
+    assert_eq!(conf.name, "anna");
+    assert_eq!(conf.log.level, "debug");
+    assert_eq!(provenance.iter().collect::<Vec<_>>(), [
+        ("name", &ValueSource::Env { key: "PROVENANCE_TEST_NAME".into() }),
+        ("log.stdout", &ValueSource::Default),
+        ("log.level", &ValueSource::Preloaded { index: 0 }),
+        ("log.file", &ValueSource::Preloaded { index: 1 }),
+    ]);
+}
+
+#[test]
+fn unset_optional_has_no_entry() {
+    let (conf, provenance) = Conf::builder()
+        .preloaded(PartialConf { name: Some("x".into()), ..Default::default() })
+        .load_with_provenance()
+        .unwrap();
+
+    assert!(conf.log.stdout);
+    assert_eq!(conf.log.file, None);
+    assert_eq!(provenance.get("name"), Some(&ValueSource::Preloaded { index: 0 }));
+    assert_eq!(provenance.get("log.file"), None);
+    assert_eq!(provenance.get("log.nope"), None);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn file() {
+    let path = std::env::temp_dir().join("confique-provenance-test.toml");
+    std::fs::write(&path, "name = \"fox\"\n[log]\nstdout = false\n").unwrap();
+
+    let (conf, provenance) = Conf::builder().file(&path).load_with_provenance().unwrap();
+    assert!(!conf.log.stdout);
+    assert_eq!(provenance.get("name"), Some(&ValueSource::File(path.clone())));
+    assert_eq!(provenance.get("log.stdout"), Some(&ValueSource::File(path.clone())));
+    assert_eq!(provenance.get("log.level"), Some(&ValueSource::Default));
+    assert_eq!(
+        provenance.get("name").unwrap().to_string(),
+        format!("file '{}'", path.display()),
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[derive(Debug, Config)]
+#[config(env_prefix = "APP_")]
+struct EnvConf {
+    #[config(env_file_suffix)]
+    token: String,
+
+    #[config(alias_env = "OLD_PORT")]
+    port: u16,
+}
+
+#[test]
+fn env_file_suffix_and_alias() {
+    let path = std::env::temp_dir().join("confique-provenance-test-token");
+    std::fs::write(&path, "secret\n").unwrap();
+
+    let (conf, provenance) = EnvConf::builder()
+        .env_from([
+            ("APP_TOKEN_FILE".to_owned(), path.display().to_string()),
+            ("APP_OLD_PORT".to_owned(), "80".to_owned()),
+        ])
+        .load_with_provenance()
+        .unwrap();
+    assert_eq!(conf.token, "secret");
+    assert_eq!(conf.port, 80);
+    assert_eq!(provenance.iter().collect::<Vec<_>>(), [
+        ("token", &ValueSource::Env { key: "APP_TOKEN_FILE".into() }),
+        ("port", &ValueSource::Env { key: "APP_OLD_PORT".into() }),
+    ]);
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,161 @@
+#![cfg(all(feature = "toml", feature = "yaml", feature = "json5"))]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use confique::{json5, toml, yaml, Config, ErrorKind, Partial};
+use common::{vars, write_file};
+
+
+#[derive(Debug, Config)]
+#[config(env_prefix = "APP_")]
+#[config(rename_all = "kebab-case")]
+struct Conf {
+    /// The address to listen on.
+    #[config(default = "127.0.0.1")]
+    listen_addr: String,
+
+    #[config(rename = "port")]
+    listen_port: u16,
+
+    #[config(nested)]
+    log_settings: LogConf,
+}
+
+#[derive(Debug, Config)]
+#[config(rename_all = "camelCase")]
+struct LogConf {
+    #[config(default = false)]
+    write_to_stdout: bool,
+
+    #[config(env = "LOG_LEVEL")]
+    max_level: Option<String>,
+}
+
+#[test]
+fn meta() {
+    let names = Conf::META.fields.iter().map(|f| f.name).collect::<Vec<_>>();
+    assert_eq!(names, ["listen-addr", "port", "log-settings"]);
+    let names = LogConf::META.fields.iter().map(|f| f.name).collect::<Vec<_>>();
+    assert_eq!(names, ["writeToStdout", "maxLevel"]);
+}
+
+#[test]
+fn files() {
+    let path = write_file("rename", "conf.yaml", "listen-addr: 0.0.0.0\nport: 80\nlog-settings:\n  \
+        writeToStdout: true\n  maxLevel: debug\n");
+    let conf = Conf::builder().file(&path).strict().load().unwrap();
+    assert_eq!(conf.listen_addr, "0.0.0.0");
+    assert_eq!(conf.listen_port, 80);
+    assert_eq!(conf.log_settings.write_to_stdout, true);
+    assert_eq!(conf.log_settings.max_level.as_deref(), Some("debug"));
+
+    // The Rust names are not accepted anymore.
+    let path = write_file("rename", "rust-names.toml", "listen_port = 80\n");
+    let partial = confique::File::new(&path).unwrap()
+        .load::<<Conf as Config>::Partial>()
+        .ok()
+        .unwrap();
+    assert!(partial.is_empty());
+}
+
+#[test]
+fn error_paths() {
+    let err = Conf::builder().load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingValue);
+    assert_eq!(err.field_path(), Some("port"));
+
+    let content = "port = 80\n[log-settings]\nwriteToStdout = 3\n";
+    let path = write_file("rename", "invalid.toml", content);
+    let err = Conf::builder().file(&path).load().unwrap_err();
+    assert_eq!(err.field_path(), Some("log-settings.writeToStdout"));
+
+    let content = "port = 80\n[log-settings]\nwriteToStdot = true\n";
+    let path = write_file("rename", "typo.toml", content);
+    let err = Conf::builder().file(&path).strict().load().unwrap_err();
+    assert!(err.to_string().contains("(did you mean `log-settings.writeToStdout`?)"), "{err}");
+}
+
+#[test]
+fn env_keys() {
+    assert_eq!(Conf::META.env_keys(), [
+        ("listen-addr".to_owned(), "APP_LISTEN_ADDR".to_owned()),
+        ("port".to_owned(), "APP_PORT".to_owned()),
+        ("log-settings.writeToStdout".to_owned(), "APP_LOG_SETTINGS_WRITE_TO_STDOUT".to_owned()),
+        ("log-settings.maxLevel".to_owned(), "APP_LOG_SETTINGS_LOG_LEVEL".to_owned()),
+    ]);
+
+    let conf = Conf::builder()
+        .env_from(vars(&[("APP_PORT", "80"), ("APP_LOG_SETTINGS_WRITE_TO_STDOUT", "true")]))
+        .load()
+        .unwrap();
+    assert_eq!(conf.listen_port, 80);
+    assert_eq!(conf.log_settings.write_to_stdout, true);
+
+    let err = Conf::builder().env_from(vars(&[("APP_PORT", "x")])).load().unwrap_err();
+    assert_eq!(err.field_path(), Some("port"));
+}
+
+#[test]
+fn templates() {
+    let out = toml::template::<Conf>(toml::FormatOptions::default());
+    assert!(out.contains("# The address to listen on.\n"), "{out}");
+    assert!(out.contains("#listen-addr = \"127.0.0.1\"\n"), "{out}");
+    assert!(out.contains("#port =\n"), "{out}");
+    assert!(out.contains("[log-settings]\n"), "{out}");
+    assert!(out.contains("#writeToStdout = false\n"), "{out}");
+
+    let out = yaml::template::<Conf>(yaml::FormatOptions::default());
+    assert!(out.contains("#listen-addr: 127.0.0.1\n"), "{out}");
+    assert!(out.contains("log-settings:\n"), "{out}");
+    assert!(out.contains("#maxLevel:\n"), "{out}");
+
+    let out = json5::template::<Conf>(json5::FormatOptions::default());
+    assert!(out.contains("//\"listen-addr\": \"127.0.0.1\",\n"), "{out}");
+    assert!(out.contains("\"log-settings\": {\n"), "{out}");
+}
+
+#[derive(Config)]
+#[allow(dead_code)]
+struct QuotedConf {
+    #[config(rename = "1st value", default = 1)]
+    first: u32,
+
+    #[config(nested, rename = "my section")]
+    section: QuotedInner,
+
+    #[config(rename = "a: b", default = 2)]
+    colon: u32,
+
+    #[config(rename = "#x")]
+    hash: Option<u32>,
+}
+
+#[derive(Config)]
+#[allow(dead_code)]
+#[config(rename_all = "kebab-case")]
+struct QuotedInner {
+    #[config(default = true)]
+    is_enabled: bool,
+}
+
+#[test]
+fn quoted_keys_in_templates() {
+    let out = toml::template::<QuotedConf>(toml::FormatOptions::default());
+    assert!(out.contains("#\"1st value\" = 1\n"), "{out}");
+    assert!(out.contains("[\"my section\"]\n"), "{out}");
+    assert!(out.contains("#is-enabled = true\n"), "{out}");
+
+    let out = json5::template::<QuotedConf>(json5::FormatOptions::default());
+    assert!(out.contains("//\"1st value\": 1,\n"), "{out}");
+    assert!(out.contains("\"my section\": {\n"), "{out}");
+    assert!(out.contains("//\"is-enabled\": true,\n"), "{out}");
+
+    let out = yaml::template::<QuotedConf>(yaml::FormatOptions::default());
+    assert!(out.contains("#1st value: 1\n"), "{out}");
+    assert!(out.contains("#'a: b': 2\n"), "{out}");
+    assert!(out.contains("#'#x':\n"), "{out}");
+    assert!(out.contains("my section:\n"), "{out}");
+    assert!(out.contains("  #is-enabled: true\n"), "{out}");
+}
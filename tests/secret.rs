@@ -0,0 +1,159 @@
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use std::collections::HashMap;
+
+use confique::{Config, Partial, ValueSource};
+use common::vars;
+
+
+#[derive(Config)]
+#[config(debug)]
+struct Conf {
+    #[config(env = "SECRET_TEST_USER")]
+    user: String,
+
+    #[config(env = "SECRET_TEST_TOKEN", secret)]
+    token: u64,
+
+    #[config(nested)]
+    db: DbConf,
+}
+
+#[derive(Config)]
+#[config(debug)]
+struct DbConf {
+    #[config(env = "SECRET_TEST_DB_PASSWORD", secret)]
+    password: Option<String>,
+
+    #[config(env = "SECRET_TEST_DB_PORT", parse_env = parse_port, secret)]
+    port: Option<u16>,
+}
+
+fn parse_port(s: &str) -> Result<u16, std::num::ParseIntError> {
+    s.parse()
+}
+
+type PartialConf = <Conf as Config>::Partial;
+
+#[test]
+fn meta() {
+    assert!(!Conf::META.fields[0].secret);
+    assert!(Conf::META.fields[1].secret);
+    assert!(DbConf::META.fields[0].secret);
+}
+
+#[test]
+fn debug() {
+    let conf = Conf::builder()
+        .env_from(vars(&[
+            ("SECRET_TEST_USER", "anna"),
+            ("SECRET_TEST_TOKEN", "1234"),
+            ("SECRET_TEST_DB_PASSWORD", "hunter2"),
+        ]))
+        .load()
+        .unwrap();
+    assert_eq!(conf.user, "anna");
+    assert_eq!(conf.token, 1234);
+    assert_eq!(conf.db.port, None);
+    assert_eq!(conf.db.password.as_deref(), Some("hunter2"));
+    assert_eq!(
+        format!("{conf:?}"),
+        "Conf { user: \"anna\", token: \"<redacted>\", \
+            db: DbConf { password: \"<redacted>\", port: \"<redacted>\" } }",
+    );
+
+    let source = vars(&[("SECRET_TEST_TOKEN", "1234")]);
+    let partial = PartialConf::from_env_source(&source).unwrap();
+    assert_eq!(
+        format!("{partial:?}"),
+        "PartialConf { user: None, token: Some(\"<redacted>\"), \
+            db: PartialDbConf { password: None, port: None } }",
+    );
+}
+
+#[derive(Config)]
+#[config(debug, partial_attr(derive(Clone)), env_prefix = "SECRET_TEST_LIST_")]
+struct AttrList {
+    #[config(secret)]
+    key: String,
+}
+
+#[test]
+fn debug_in_attribute_list() {
+    let partial = <AttrList as Config>::Partial::from_env_source(&HashMap::from([
+        ("SECRET_TEST_LIST_KEY".to_owned(), "hunter2".to_owned()),
+    ])).unwrap();
+    assert_eq!(
+        format!("{:?}", partial.clone()),
+        "PartialAttrList { key: Some(\"<redacted>\") }",
+    );
+    let conf = AttrList::from_partial(partial).unwrap();
+    assert_eq!(conf.key, "hunter2");
+    assert_eq!(format!("{conf:?}"), "AttrList { key: \"<redacted>\" }");
+}
+
+#[test]
+fn errors_do_not_contain_value() {
+    let err = |pairs: &[(&str, &str)]| {
+        Conf::builder().env_from(vars(pairs)).load().unwrap_err().to_string()
+    };
+
+    assert_eq!(
+        err(&[("SECRET_TEST_TOKEN", "s3cr3t")]),
+        "failed to deserialize value `token` from environment variable \
+            `SECRET_TEST_TOKEN`: invalid value (details are redacted as the value is secret)",
+    );
+    assert_eq!(
+        err(&[("SECRET_TEST_DB_PORT", "s3cr3t")]),
+        "failed to parse environment variable `SECRET_TEST_DB_PORT` into field \
+            `db.port`: invalid value (details are redacted as the value is secret)",
+    );
+
+    let err = Conf::builder().overrides(["token=s3cr3t"]).load().unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "failed to deserialize value `token` from override `token=<redacted>`: \
+            invalid value (details are redacted as the value is secret)",
+    );
+
+    let err = Conf::builder().args(["app", "--token", "s3cr3t"]).load().unwrap_err();
+    assert!(!err.to_string().contains("s3cr3t"));
+
+    #[cfg(feature = "toml")]
+    {
+        let path = std::env::temp_dir().join("confique-secret-test.toml");
+        std::fs::write(&path, "user = \"anna\"\ntoken = \"s3cr3t\"\n").unwrap();
+        let err = Conf::builder().file(&path).load().unwrap_err();
+        assert_eq!(err.field_path(), Some("token"));
+        let location = err.location().unwrap();
+        assert_eq!((location.line(), location.column()), (2, 9));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "failed to deserialize value `token` from file '{}' at line 2, column 9: \
+                    invalid value (details are redacted as the value is secret)",
+                path.display(),
+            ),
+        );
+        assert_eq!(format!("{err:#}"), err.to_string());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn provenance() {
+    let (_, provenance) = Conf::builder()
+        .overrides(["token=1234", "user=anna"])
+        .load_with_provenance()
+        .unwrap();
+    assert_eq!(
+        provenance.get("token"),
+        Some(&ValueSource::Override { value: "token=<redacted>".into() }),
+    );
+    assert_eq!(
+        provenance.get("user"),
+        Some(&ValueSource::Override { value: "user=anna".into() }),
+    );
+}
@@ -0,0 +1,149 @@
+#![cfg(all(feature = "toml", feature = "yaml", feature = "json5"))]
+
+mod common;
+
+use std::{collections::HashMap, fs};
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, ErrorKind, File, FileFormat, Partial};
+use common::write_file;
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(default = "app")]
+    name: String,
+
+    #[config(default = {})]
+    labels: HashMap<String, String>,
+
+    #[config(nested)]
+    log: LogConf,
+}
+
+#[derive(Debug, Config)]
+struct LogConf {
+    #[config(default = false)]
+    stdout: bool,
+
+    #[config(default = "info")]
+    level: String,
+}
+
+type PartialConf = <Conf as Config>::Partial;
+
+fn messages(err: &confique::Error) -> Vec<String> {
+    err.errors().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn unknown_keys_are_ignored_by_default() {
+    let path = write_file("strict", "lenient.toml", "nmae = \"x\"\n[log]\nstdot = true");
+    let conf = Conf::builder().file(&path).load().unwrap();
+    assert_eq!(conf.name, "app");
+    assert_eq!(conf.log.stdout, false);
+    assert_eq!(conf.log.level, "info");
+    assert!(conf.labels.is_empty());
+}
+
+#[test]
+fn toml() {
+    let path = write_file("strict", "typos.toml", "nme = \"x\"\nfoo = 3\n[log]\nstdot = true");
+    let err = File::new(&path).unwrap().strict().load::<PartialConf>().err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::Multiple);
+    assert_eq!(messages(&err), [
+        format!(
+            "unknown configuration key `nme` in file '{}' (did you mean `name`?)",
+            path.display(),
+        ),
+        format!("unknown configuration key `foo` in file '{}'", path.display()),
+        format!(
+            "unknown configuration key `log.stdot` in file '{}' (did you mean `log.stdout`?)",
+            path.display(),
+        ),
+    ]);
+
+    let errors = err.errors().collect::<Vec<_>>();
+    assert!(errors.iter().all(|e| e.kind() == ErrorKind::UnknownKey));
+    assert!(errors.iter().all(|e| e.file_path() == Some(&*path)));
+}
+
+#[test]
+fn yaml_and_json5() {
+    let path = write_file("strict", "typo.yaml", "log:\n  levl: debug");
+    let err = File::new(&path).unwrap().strict().load::<PartialConf>().err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::UnknownKey);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "unknown configuration key `log.levl` in file '{}' (did you mean `log.level`?)",
+            path.display(),
+        ),
+    );
+
+    let path = write_file("strict", "typo.json5", "{ name: 'x', lg: { stdout: true } }");
+    let err = File::with_format(&path, FileFormat::Json5)
+        .strict()
+        .load::<PartialConf>()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "unknown configuration key `lg` in file '{}' (did you mean `log`?)",
+            path.display(),
+        ),
+    );
+}
+
+#[test]
+fn valid_files_and_map_values() {
+    let content = "name = \"x\"\n[labels]\nanything = \"goes\"\n[log]\nstdout = true";
+    let path = write_file("strict", "valid.toml", content);
+    let partial = File::new(&path).unwrap().strict().load::<PartialConf>().ok().unwrap();
+    assert_eq!(partial.name.as_deref(), Some("x"));
+    assert_eq!(partial.log.stdout, Some(true));
+    assert!(!partial.is_empty());
+
+    let conf = Conf::builder().file(&path).strict().load().unwrap();
+    assert_eq!(conf.labels["anything"], "goes");
+}
+
+#[test]
+fn builder() {
+    let path = write_file("strict", "builder.toml", "[log]\nstdot = true");
+    let err = Conf::builder()
+        .strict()
+        .overrides(["name=x"])
+        .file(&path)
+        .load()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnknownKey);
+    assert_eq!(err.field_path(), None);
+    assert!(err.to_string().contains("(did you mean `log.stdout`?)"));
+}
+
+#[test]
+fn key_dir() {
+    let dir = std::env::temp_dir().join("confique-strict-test-key-dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("log__level"), "debug\n").unwrap();
+    fs::write(dir.join("log.stdot"), "true\n").unwrap();
+    fs::write(dir.join(".hidden"), "ignored").unwrap();
+
+    let conf = Conf::builder().key_dir(&dir).load().unwrap();
+    assert_eq!(conf.log.level, "debug");
+
+    let err = Conf::builder().key_dir(&dir).strict().load().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnknownKey);
+    assert_eq!(err.file_path(), Some(&*dir.join("log.stdot")));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "unknown configuration key `log.stdot` in key file '{}' (did you mean `log.stdout`?)",
+            dir.join("log.stdot").display(),
+        ),
+    );
+}
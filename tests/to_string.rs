@@ -0,0 +1,375 @@
+#![cfg(all(feature = "toml", feature = "yaml", feature = "json5"))]
+
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use pretty_assertions::assert_eq;
+use serde::{Deserialize, Serialize};
+
+use confique::{json5, toml, yaml, Config, ErrorKind};
+
+
+#[derive(Config)]
+#[config(serialize)]
+#[config(env_prefix = "APP_")]
+struct Conf {
+    /// The port to listen on.
+    #[config(default = 8080)]
+    port: u16,
+
+    #[config(secret)]
+    password: String,
+
+    #[config(rename = "allowed-hosts", default = [])]
+    hosts: Vec<String>,
+
+    labels: BTreeMap<String, u32>,
+
+    mode: Mode,
+
+    #[config(nested)]
+    log: LogConf,
+}
+
+#[derive(Config)]
+#[config(serialize)]
+struct LogConf {
+    #[config(default = 0.5)]
+    ratio: f32,
+
+    file: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    Fast,
+}
+
+fn conf() -> Conf {
+    Conf {
+        port: 80,
+        password: "hunter2".into(),
+        hosts: vec!["a.example".into(), "b.example".into()],
+        labels: [("x".to_owned(), 1), ("y".to_owned(), 2)].into(),
+        mode: Mode::Fast,
+        log: LogConf { ratio: 0.1, file: None },
+    }
+}
+
+#[test]
+fn toml() {
+    let out = toml::to_string(&conf(), toml::FormatOptions::default()).unwrap();
+    assert_eq!(out, "\
+        # The port to listen on.\n\
+        #\n\
+        # Can also be specified via environment variable `APP_PORT`.\n\
+        #\n\
+        # Default value: 8080\n\
+        port = 80\n\
+        \n\
+        # Can also be specified via environment variable `APP_PASSWORD`.\n\
+        #password = <redacted>\n\
+        \n\
+        # Can also be specified via environment variable `APP_ALLOWED_HOSTS`.\n\
+        # Default value: []\n\
+        allowed-hosts = [\"a.example\", \"b.example\"]\n\
+        \n\
+        # Can also be specified via environment variable `APP_LABELS`.\n\
+        labels = { x = 1, y = 2 }\n\
+        \n\
+        # Can also be specified via environment variable `APP_MODE`.\n\
+        mode = \"fast\"\n\
+        \n\
+        [log]\n\
+        # Can also be specified via environment variable `APP_LOG_RATIO`.\n\
+        # Default value: 0.5\n\
+        ratio = 0.1\n\
+        \n\
+        # Can also be specified via environment variable `APP_LOG_FILE`.\n\
+        #file =\n\
+    ");
+}
+
+#[test]
+fn no_comments() {
+    let mut options = toml::FormatOptions::default();
+    options.general.comments = false;
+    let out = toml::to_string(&conf(), options).unwrap();
+    assert_eq!(out, "\
+        port = 80\n\
+        #password = <redacted>\n\
+        allowed-hosts = [\"a.example\", \"b.example\"]\n\
+        labels = { x = 1, y = 2 }\n\
+        mode = \"fast\"\n\
+        \n\
+        [log]\n\
+        ratio = 0.1\n\
+        #file =\n\
+    ");
+}
+
+#[test]
+fn yaml() {
+    let mut options = yaml::FormatOptions::default();
+    options.general.comments = false;
+    let out = yaml::to_string(&conf(), options).unwrap();
+    assert_eq!(out, "\
+        port: 80\n\
+        #password: <redacted>\n\
+        allowed-hosts: [a.example, b.example]\n\
+        labels: { x: 1, y: 2 }\n\
+        mode: fast\n\
+        \n\
+        log:\n  \
+          ratio: 0.1\n  \
+          #file:\n\
+    ");
+}
+
+#[test]
+fn json5() {
+    let mut options = json5::FormatOptions::default();
+    options.general.comments = false;
+    let out = json5::to_string(&conf(), options).unwrap();
+    assert_eq!(out, "\
+        {\n  \
+          port: 80,\n  \
+          //password: <redacted>,\n  \
+          \"allowed-hosts\": [\"a.example\",\"b.example\"],\n  \
+          labels: {\"x\":1,\"y\":2},\n  \
+          mode: \"fast\",\n\
+          \n  \
+          log: {\n    \
+            ratio: 0.1,\n    \
+            //file: ,\n  \
+          },\n\
+        }\n\
+    ");
+}
+
+#[test]
+fn round_trip() {
+    let dir = std::env::temp_dir().join("confique-to-string-test");
+    fs::create_dir_all(&dir).unwrap();
+
+    let outputs = [
+        ("conf.toml", toml::to_string(&conf(), Default::default()).unwrap()),
+        ("conf.yaml", yaml::to_string(&conf(), Default::default()).unwrap()),
+        ("conf.json5", json5::to_string(&conf(), Default::default()).unwrap()),
+    ];
+    for (name, content) in outputs {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+
+        // The secret is not included, so it has to be given separately.
+        let loaded = Conf::builder()
+            .env_from([("APP_PASSWORD".to_owned(), "x".to_owned())])
+            .file(&path)
+            .strict()
+            .load()
+            .unwrap();
+        assert_eq!(loaded.port, 80, "{name}");
+        assert_eq!(loaded.password, "x", "{name}");
+        assert_eq!(loaded.hosts, ["a.example", "b.example"], "{name}");
+        assert_eq!(loaded.labels, conf().labels, "{name}");
+        assert_eq!(loaded.mode, Mode::Fast, "{name}");
+        assert_eq!(loaded.log.ratio, 0.1, "{name}");
+        assert_eq!(loaded.log.file, None, "{name}");
+    }
+}
+
+#[test]
+fn unrepresentable_value() {
+    #[derive(Config)]
+    #[config(serialize)]
+    struct Conf {
+        values: Vec<Option<u32>>,
+    }
+
+    let conf = Conf { values: vec![Some(1), None] };
+    let err = toml::to_string(&conf, Default::default()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Serialization);
+    assert_eq!(err.field_path(), Some("values"));
+    assert!(err.to_string().starts_with("failed to serialize value `values`: "), "{err}");
+
+    // YAML and JSON5 support `null`.
+    let out = yaml::to_string(&conf, Default::default()).unwrap();
+    assert!(out.contains("values: [1, null]\n"), "{out}");
+}
+
+#[test]
+fn provenance() {
+    let dir = std::env::temp_dir().join("confique-to-string-provenance-test");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("conf.toml");
+    fs::write(&path, "labels = { a = 1 }\nmode = \"fast\"\n[log]\nratio = 0.25\n").unwrap();
+
+    let (conf, provenance) = Conf::builder()
+        .env_from([
+            ("APP_PORT".to_owned(), "80".to_owned()),
+            ("APP_PASSWORD".to_owned(), "hunter2".to_owned()),
+        ])
+        .file(&path)
+        .load_with_provenance()
+        .unwrap();
+
+    let out = toml::to_string_with_provenance(&conf, &provenance, Default::default()).unwrap();
+    assert_eq!(out, format!("\
+        # The port to listen on.\n\
+        #\n\
+        # Can also be specified via environment variable `APP_PORT`.\n\
+        #\n\
+        # Default value: 8080\n\
+        #\n\
+        # Source: environment variable `APP_PORT`\n\
+        port = 80\n\
+        \n\
+        # Can also be specified via environment variable `APP_PASSWORD`.\n\
+        #\n\
+        # Source: environment variable `APP_PASSWORD`\n\
+        #password = <redacted>\n\
+        \n\
+        # Can also be specified via environment variable `APP_ALLOWED_HOSTS`.\n\
+        # Default value: []\n\
+        #\n\
+        # Source: default value\n\
+        allowed-hosts = []\n\
+        \n\
+        # Can also be specified via environment variable `APP_LABELS`.\n\
+        #\n\
+        # Source: file '{path}'\n\
+        labels = {{ a = 1 }}\n\
+        \n\
+        # Can also be specified via environment variable `APP_MODE`.\n\
+        #\n\
+        # Source: file '{path}'\n\
+        mode = \"fast\"\n\
+        \n\
+        [log]\n\
+        # Can also be specified via environment variable `APP_LOG_RATIO`.\n\
+        # Default value: 0.5\n\
+        #\n\
+        # Source: file '{path}'\n\
+        ratio = 0.25\n\
+        \n\
+        # Can also be specified via environment variable `APP_LOG_FILE`.\n\
+        #file =\n\
+    ", path = path.display()));
+
+    // Without other comments.
+    let mut options = yaml::FormatOptions::default();
+    options.general.comments = false;
+    let out = yaml::to_string_with_provenance(&conf, &provenance, options).unwrap();
+    assert!(out.starts_with("# Source: environment variable `APP_PORT`\nport: 80\n"), "{out}");
+    assert!(out.contains("  # Source: file '"), "{out}");
+
+    let mut options = json5::FormatOptions::default();
+    options.general.comments = false;
+    let out = json5::to_string_with_provenance(&conf, &provenance, options).unwrap();
+    assert!(out.contains("  // Source: default value\n  \"allowed-hosts\": [],\n"), "{out}");
+}
+
+#[derive(Config)]
+#[config(serialize)]
+#[config(env_prefix = "APP_")]
+struct EnvConf {
+    #[config(env_file_suffix)]
+    token: String,
+
+    #[config(alias_env = "OLD_PORT")]
+    port: u16,
+}
+
+#[test]
+fn provenance_env_file_suffix_and_alias() {
+    let dir = std::env::temp_dir().join("confique-to-string-provenance-test");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("token");
+    fs::write(&path, "abc\n").unwrap();
+
+    let (conf, provenance) = EnvConf::builder()
+        .env_from([
+            ("APP_TOKEN_FILE".to_owned(), path.display().to_string()),
+            ("APP_OLD_PORT".to_owned(), "80".to_owned()),
+        ])
+        .load_with_provenance()
+        .unwrap();
+
+    let mut options = toml::FormatOptions::default();
+    options.general.comments = false;
+    let out = toml::to_string_with_provenance(&conf, &provenance, options).unwrap();
+    assert_eq!(out, "\
+        # Source: environment variable `APP_TOKEN_FILE`\n\
+        token = \"abc\"\n\
+        # Source: environment variable `APP_OLD_PORT`\n\
+        port = 80\n\
+    ");
+}
+
+#[derive(Config)]
+#[config(serialize)]
+#[allow(dead_code)]
+struct SecretConf {
+    #[config(secret)]
+    token: String,
+
+    #[config(secret)]
+    key: Option<String>,
+
+    #[config(secret)]
+    old_key: Option<String>,
+}
+
+#[test]
+fn serialize_redacts_secrets() {
+    let conf = SecretConf {
+        token: "hunter2".into(),
+        key: Some("s3cr3t".into()),
+        old_key: None,
+    };
+    let out = serde_yaml::to_string(&conf).unwrap();
+    assert_eq!(out, "token: <redacted>\nkey: <redacted>\nold_key: null\n");
+}
+
+#[derive(Config)]
+#[config(serialize)]
+struct MultiLineConf {
+    #[config(nested)]
+    outer: MultiLineOuter,
+}
+
+#[derive(Config)]
+#[config(serialize)]
+struct MultiLineOuter {
+    #[config(nested)]
+    inner: MultiLineInner,
+}
+
+#[derive(Config)]
+#[config(serialize)]
+struct MultiLineInner {
+    text: String,
+}
+
+#[test]
+fn multi_line_string_round_trip() {
+    let dir = std::env::temp_dir().join("confique-to-string-multi-line-test");
+    fs::create_dir_all(&dir).unwrap();
+
+    for text in ["first: line\n  indented \"quoted\"\\\nlast\n", "a\tb\r\nc"] {
+        let conf = MultiLineConf {
+            outer: MultiLineOuter { inner: MultiLineInner { text: text.into() } },
+        };
+        let outputs = [
+            ("conf.toml", toml::to_string(&conf, Default::default()).unwrap()),
+            ("conf.yaml", yaml::to_string(&conf, Default::default()).unwrap()),
+            ("conf.json5", json5::to_string(&conf, Default::default()).unwrap()),
+        ];
+        for (name, content) in outputs {
+            let path = dir.join(name);
+            fs::write(&path, &content).unwrap();
+            let loaded = MultiLineConf::builder().file(&path).strict().load().unwrap();
+            assert_eq!(loaded.outer.inner.text, text, "{name}:\n{content}");
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use pretty_assertions::assert_eq;
+
+use confique::Config;
+
+
+#[derive(Debug, Config)]
+struct Conf {
+    #[config(nested)]
+    http: HttpConf,
+
+    #[config(validate = validate_name)]
+    name: Option<String>,
+}
+
+#[derive(Debug, Config)]
+#[config(validate = validate_http)]
+struct HttpConf {
+    #[config(default = 8080, validate = validate_port)]
+    port: u16,
+
+    #[config(default = 1)]
+    min_workers: u32,
+
+    #[config(default = 4)]
+    max_workers: u32,
+}
+
+fn validate_port(port: &u16) -> Result<(), &'static str> {
+    match *port {
+        0 => Err("port must not be 0"),
+        _ => Ok(()),
+    }
+}
+
+fn validate_name(name: &String) -> Result<(), String> {
+    match name.is_empty() {
+        true => Err(format!("name must not be empty, got {name:?}")),
+        false => Ok(()),
+    }
+}
+
+fn validate_http(http: &HttpConf) -> Result<(), &'static str> {
+    if http.min_workers > http.max_workers {
+        return Err("`min_workers` must not be larger than `max_workers`");
+    }
+    Ok(())
+}
+
+fn load(overrides: &[&str]) -> Result<Conf, confique::Error> {
+    Conf::builder().overrides(overrides.iter().copied()).load()
+}
+
+#[test]
+fn valid() {
+    let conf = load(&["http.port=80"]).unwrap();
+    assert_eq!(conf.http.port, 80);
+    assert_eq!(conf.name, None);
+
+    let conf = load(&["name=foo", "http.min_workers=4"]).unwrap();
+    assert_eq!(conf.name.as_deref(), Some("foo"));
+    assert_eq!(conf.http.min_workers, 4);
+}
+
+#[test]
+fn invalid() {
+    let err = |overrides: &[&str]| load(overrides).unwrap_err().to_string();
+
+    assert_eq!(
+        err(&["http.port=0"]),
+        "invalid configuration value `http.port`: port must not be 0",
+    );
+    assert_eq!(
+        err(&["name="]),
+        "invalid configuration value `name`: name must not be empty, got \"\"",
+    );
+    assert_eq!(
+        err(&["http.min_workers=5"]),
+        "invalid configuration value `http`: \
+            `min_workers` must not be larger than `max_workers`",
+    );
+    assert_eq!(
+        HttpConf::builder().overrides(["min_workers=5"]).load().unwrap_err().to_string(),
+        "invalid configuration: `min_workers` must not be larger than `max_workers`",
+    );
+}
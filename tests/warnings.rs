@@ -0,0 +1,144 @@
+#![cfg(feature = "toml")]
+
+mod common;
+
+use std::fs;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, ValueSource, Warning};
+use common::{temp_dir, vars};
+
+
+#[derive(Debug, Config)]
+#[config(env_prefix = "APP_")]
+struct Conf {
+    #[config(default = 8080)]
+    port: u16,
+
+    #[config(nested)]
+    log: LogConf,
+}
+
+#[derive(Debug, Config)]
+struct LogConf {
+    #[config(default = false)]
+    stdout: bool,
+}
+
+#[test]
+fn no_warnings() {
+    let (conf, warnings) = Conf::builder()
+        .env_from(vars(&[("APP_PORT", "80"), ("OTHER_PORT", "1")]))
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(conf.log.stdout, false);
+    assert_eq!(warnings, []);
+}
+
+#[test]
+fn unknown_keys_in_file() {
+    let dir = temp_dir("warnings-file");
+    let path = dir.join("app.toml");
+    fs::write(&path, "port = 80\n[log]\nstdot = true").unwrap();
+
+    let (conf, warnings) = Conf::builder().file(&path).load_with_warnings().unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(warnings, [Warning::UnknownKey {
+        path: "log.stdot".into(),
+        suggestion: Some("log.stdout".into()),
+        source: ValueSource::File(path.clone()),
+    }]);
+    assert_eq!(warnings[0].path(), Some("log.stdot"));
+    assert_eq!(warnings[0].source(), Some(&ValueSource::File(path.clone())));
+    assert_eq!(
+        warnings[0].to_string(),
+        format!(
+            "unknown configuration key `log.stdot` in file '{}' (did you mean `log.stdout`?)",
+            path.display(),
+        ),
+    );
+
+    // In strict mode, that's an error instead.
+    assert!(Conf::builder().file(&path).strict().load_with_warnings().is_err());
+}
+
+#[test]
+fn unknown_files_in_key_dir() {
+    let dir = temp_dir("warnings-key-dir");
+    fs::write(dir.join("prt"), "80").unwrap();
+
+    let (_, warnings) = Conf::builder().key_dir(&dir).load_with_warnings().unwrap();
+    assert_eq!(warnings, [Warning::UnknownKey {
+        path: "prt".into(),
+        suggestion: Some("port".into()),
+        source: ValueSource::KeyFile(dir.join("prt")),
+    }]);
+}
+
+#[test]
+fn unknown_env_vars() {
+    let (conf, warnings) = Conf::builder()
+        .env_from(vars(&[
+            ("APP_PROT", "80"),
+            ("APP_LOG_STDOUT", "true"),
+            ("APP_PORT_FILE", "/run/secrets/port"),
+            ("APP_SOMETHING_ELSE", "x"),
+            ("PORT", "1"),
+        ]))
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(conf.port, 8080);
+    assert_eq!(conf.log.stdout, true);
+    assert_eq!(warnings, [
+        Warning::UnknownEnvVar {
+            source: ValueSource::Env { key: "APP_PROT".into() },
+            suggestion: Some("APP_PORT".into()),
+        },
+        Warning::UnknownEnvVar {
+            source: ValueSource::Env { key: "APP_SOMETHING_ELSE".into() },
+            suggestion: None,
+        },
+    ]);
+    assert_eq!(warnings[0].path(), None);
+    assert_eq!(
+        warnings[0].to_string(),
+        "environment variable `APP_PROT` does not correspond to any configuration value \
+            (did you mean `APP_PORT`?)",
+    );
+}
+
+#[test]
+fn unknown_vars_in_env_file() {
+    let dir = temp_dir("warnings-env-file");
+    let path = dir.join(".env");
+    fs::write(&path, "APP_LOG_STDOT=true\n").unwrap();
+
+    let (_, warnings) = Conf::builder().env_file(&path).load_with_warnings().unwrap();
+    assert_eq!(warnings, [Warning::UnknownEnvVar {
+        source: ValueSource::EnvFile { path: path.clone(), key: "APP_LOG_STDOT".into() },
+        suggestion: Some("APP_LOG_STDOUT".into()),
+    }]);
+}
+
+#[test]
+fn missing_optional_files() {
+    let dir = temp_dir("warnings-missing");
+    let (conf, warnings) = Conf::builder()
+        .file(dir.join("app.toml"))
+        .dir(dir.join("conf.d"))
+        .key_dir(dir.join("keys"))
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(conf.port, 8080);
+    assert_eq!(warnings, [
+        Warning::MissingFile { path: dir.join("app.toml") },
+        Warning::MissingFile { path: dir.join("conf.d") },
+        Warning::MissingFile { path: dir.join("keys") },
+    ]);
+    assert_eq!(
+        warnings[0].to_string(),
+        format!("optional configuration file '{}' does not exist", dir.join("app.toml").display()),
+    );
+}